@@ -0,0 +1,6 @@
+MEMORY
+{
+  /* The last 8K of the 512K flash hold the settings, see src/settings_flash.rs. */
+  FLASH : ORIGIN = 0x08000000, LENGTH = 504K
+  RAM : ORIGIN = 0x20000000, LENGTH = 64K
+}
@@ -0,0 +1,31 @@
+use pmw3360_mouse::button_data::{ButtonData, MAX_BUTTONS};
+use pmw3360_mouse::debouncer::{DebounceConfig, Debouncer};
+use stm32f1xx_hal::gpio::{ErasedPin, Input, PullUp};
+
+pub type ButtonPin = ErasedPin<Input<PullUp>>;
+
+/// Reads and debounces up to `MAX_BUTTONS` active-low switches. The pin at index `n` is button
+/// `n` of `ButtonData`, see `MouseButton` for the conventional order.
+pub struct ButtonDriver<const N: usize> {
+    pins: [ButtonPin; N],
+    debouncer: Debouncer<N>,
+}
+
+impl<const N: usize> ButtonDriver<N> {
+    pub fn new(pins: [ButtonPin; N], debounce_configs: [DebounceConfig; N]) -> Self {
+        assert!(N <= MAX_BUTTONS, "Too many buttons.");
+        Self {
+            pins,
+            debouncer: Debouncer::new(debounce_configs),
+        }
+    }
+
+    /// Samples the pins and returns the debounced state as of `now_ms`.
+    pub fn get_current_data(&mut self, now_ms: u32) -> ButtonData {
+        let mut raw = ButtonData::default();
+        for (button, pin) in self.pins.iter().enumerate() {
+            raw.set_pressed(button, pin.is_low());
+        }
+        self.debouncer.update(raw, now_ms)
+    }
+}
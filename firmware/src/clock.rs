@@ -0,0 +1,36 @@
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+use cortex_m::interrupt::Mutex;
+use cortex_m::peripheral::NVIC;
+use stm32f1xx_hal::pac::{interrupt, Interrupt, TIM2};
+use stm32f1xx_hal::prelude::*;
+use stm32f1xx_hal::rcc::Clocks;
+use stm32f1xx_hal::timer::{CounterHz, Event};
+
+static MILLIS: AtomicU32 = AtomicU32::new(0);
+static TIMER: Mutex<RefCell<Option<CounterHz<TIM2>>>> = Mutex::new(RefCell::new(None));
+
+/// Starts the millisecond time base on TIM2. SysTick stays free for blocking delays.
+pub fn start(tim2: TIM2, clocks: &Clocks) {
+    let mut timer = tim2.counter_hz(clocks);
+    timer.start(1.kHz()).unwrap();
+    timer.listen(Event::Update);
+
+    cortex_m::interrupt::free(|cs| TIMER.borrow(cs).replace(Some(timer)));
+    unsafe { NVIC::unmask(Interrupt::TIM2) };
+}
+
+/// Milliseconds since `start`, wrapping after about 49 days.
+pub fn now_ms() -> u32 {
+    MILLIS.load(Ordering::Relaxed)
+}
+
+#[interrupt]
+fn TIM2() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(timer) = TIMER.borrow(cs).borrow_mut().as_mut() {
+            timer.clear_interrupt(Event::Update);
+        }
+    });
+    MILLIS.fetch_add(1, Ordering::Relaxed);
+}
@@ -0,0 +1,94 @@
+//! Vendor-defined HID interface carrying the configuration protocol. Requests arrive on an
+//! interrupt OUT endpoint, which `usbd_hid_device::Hid` doesn't have, so the class is written out
+//! here.
+
+use pmw3360_protocol::message::{REPORT_DESCRIPTOR, REPORT_SIZE};
+use usb_device::class_prelude::*;
+use usb_device::control::{Recipient, Request, RequestType};
+
+const USB_CLASS_HID: u8 = 0x03;
+const HID_DESCRIPTOR_TYPE: u8 = 0x21;
+const REPORT_DESCRIPTOR_TYPE: u8 = 0x22;
+const SET_IDLE: u8 = 0x0a;
+
+pub struct ConfigHid<'a, B: UsbBus> {
+    interface: InterfaceNumber,
+    in_endpoint: EndpointIn<'a, B>,
+    out_endpoint: EndpointOut<'a, B>,
+}
+
+impl<'a, B: UsbBus> ConfigHid<'a, B> {
+    pub fn new(allocator: &'a UsbBusAllocator<B>, poll_ms: u8) -> Self {
+        Self {
+            interface: allocator.interface(),
+            in_endpoint: allocator.interrupt(REPORT_SIZE as u16, poll_ms),
+            out_endpoint: allocator.interrupt(REPORT_SIZE as u16, poll_ms),
+        }
+    }
+
+    /// Takes a request report from the OUT endpoint, if the host sent one.
+    pub fn read_report(&mut self) -> Option<[u8; REPORT_SIZE]> {
+        let mut report = [0; REPORT_SIZE];
+        match self.out_endpoint.read(&mut report) {
+            Ok(_) => Some(report),
+            Err(_) => None,
+        }
+    }
+
+    pub fn write_report(&mut self, report: &[u8; REPORT_SIZE]) -> usb_device::Result<()> {
+        self.in_endpoint.write(report).map(|_| ())
+    }
+
+    fn is_for_interface(&self, request: &Request) -> bool {
+        request.recipient == Recipient::Interface
+            && request.index == u8::from(self.interface) as u16
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for ConfigHid<'_, B> {
+    fn get_configuration_descriptors(
+        &self,
+        writer: &mut DescriptorWriter,
+    ) -> usb_device::Result<()> {
+        let [length_low, length_high] = (REPORT_DESCRIPTOR.len() as u16).to_le_bytes();
+
+        writer.interface(self.interface, USB_CLASS_HID, 0, 0)?;
+        // HID 1.11, not localized, one report descriptor.
+        writer.write(
+            HID_DESCRIPTOR_TYPE,
+            &[
+                0x11,
+                0x01,
+                0x00,
+                0x01,
+                REPORT_DESCRIPTOR_TYPE,
+                length_low,
+                length_high,
+            ],
+        )?;
+        writer.endpoint(&self.in_endpoint)?;
+        writer.endpoint(&self.out_endpoint)
+    }
+
+    fn control_in(&mut self, xfer: ControlIn<B>) {
+        let request = *xfer.request();
+        if request.request_type == RequestType::Standard
+            && self.is_for_interface(&request)
+            && request.request == Request::GET_DESCRIPTOR
+            && (request.value >> 8) as u8 == REPORT_DESCRIPTOR_TYPE
+        {
+            xfer.accept_with_static(REPORT_DESCRIPTOR).ok();
+        }
+    }
+
+    fn control_out(&mut self, xfer: ControlOut<B>) {
+        // Hosts send SET_IDLE while binding; there are no periodic reports for it to change.
+        let request = *xfer.request();
+        if request.request_type == RequestType::Class
+            && self.is_for_interface(&request)
+            && request.request == SET_IDLE
+        {
+            xfer.accept().ok();
+        }
+    }
+}
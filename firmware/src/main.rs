@@ -0,0 +1,253 @@
+#![no_std]
+#![no_main]
+#![feature(alloc_error_handler)]
+
+pub mod button_driver;
+pub mod clock;
+pub mod config_hid;
+pub mod motion_pin;
+pub mod pmw_bus;
+pub mod settings_flash;
+pub mod usb_driver;
+pub mod wheel_driver;
+
+extern crate alloc;
+
+use alloc_cortex_m::CortexMHeap;
+use core::alloc::Layout;
+use panic_rtt_target as _;
+
+use crate::button_driver::ButtonDriver;
+use crate::pmw_bus::{PmwChipEnable, PmwSpiDevice, SharedDelay};
+use crate::settings_flash::Stm32SettingsFlash;
+use crate::usb_driver::UsbDriver;
+use crate::wheel_driver::WheelDriver;
+use cortex_m_rt::entry;
+use embedded_hal::delay::DelayNs;
+use fugit::HertzU32;
+use pmw3360_mouse::config_server::ConfigServer;
+use pmw3360_mouse::debouncer::{DebounceAlgorithm, DebounceConfig};
+use pmw3360_mouse::motion_accumulator::MotionAccumulator;
+use pmw3360_mouse::mouse::Mouse;
+use pmw3360_mouse::orientation::{Orientation, Rotation};
+use pmw3360_mouse::pmw_driver::PmwDriver;
+use pmw3360_mouse::polling_rate::ReportTimer;
+use pmw3360_mouse::power_mode::PowerMode;
+use pmw3360_mouse::quadrature::StepsPerDetent;
+use pmw3360_mouse::settings::Settings;
+use pmw3360_mouse::settings_store::SettingsStore;
+use rtt_target::{rprintln, rtt_init_print};
+use stm32f1xx_hal::pac::{CorePeripherals, Peripherals};
+use stm32f1xx_hal::spi::{Mode, Phase, Polarity, Spi};
+use stm32f1xx_hal::{prelude::*, usb};
+
+const SENSOR_RETRY_DELAY_MS: u32 = 500;
+// Reported to host tools, kept in step with Cargo.toml.
+const FIRMWARE_VERSION: [u8; 3] = [0, 1, 0];
+// Used until settings were saved to flash for the first time.
+const DEFAULT_SETTINGS: Settings = Settings {
+    // The sensor sits rotated by 180 degrees on our boards.
+    orientation: Orientation {
+        rotation: Rotation::Deg180,
+        swap_xy: false,
+        invert_x: false,
+        invert_y: false,
+    },
+    // USB powered, so latency wins over current draw by default.
+    power_mode: PowerMode::PERFORMANCE,
+    ..Settings::DEFAULT
+};
+// Main buttons react without latency, back/forward are less timing critical and get the
+// noise-proof algorithm.
+const BUTTON_DEBOUNCE: [DebounceConfig; 5] = [
+    MAIN_BUTTON_DEBOUNCE,
+    MAIN_BUTTON_DEBOUNCE,
+    MAIN_BUTTON_DEBOUNCE,
+    SIDE_BUTTON_DEBOUNCE,
+    SIDE_BUTTON_DEBOUNCE,
+];
+const MAIN_BUTTON_DEBOUNCE: DebounceConfig = DebounceConfig {
+    algorithm: DebounceAlgorithm::Eager,
+    debounce_ms: 5,
+};
+const SIDE_BUTTON_DEBOUNCE: DebounceConfig = DebounceConfig {
+    algorithm: DebounceAlgorithm::Deferred,
+    debounce_ms: 10,
+};
+// Typical mechanical scroll wheel encoders go through a full quadrature cycle per detent.
+const WHEEL_STEPS_PER_DETENT: StepsPerDetent = StepsPerDetent::Four;
+
+#[entry]
+fn main() -> ! {
+    rtt_init_print!();
+    unsafe {
+        let start = cortex_m_rt::heap_start() as usize;
+        ALLOCATOR.init(start, 16384);
+    }
+
+    let cp = CorePeripherals::take().unwrap();
+    let dp = Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+    let mut afio = dp.AFIO.constrain();
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpiob = dp.GPIOB.split();
+    let mut gpioc = dp.GPIOC.split();
+    let mut exti = dp.EXTI;
+    // Keeps the debug connection, and with it RTT, alive while the core sleeps in WFI.
+    dp.DBGMCU.cr.modify(|_, w| w.dbg_sleep().set_bit());
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(HertzU32::MHz(8))
+        .sysclk(HertzU32::MHz(72))
+        .hclk(HertzU32::MHz(72))
+        .pclk1(HertzU32::MHz(36))
+        .pclk2(HertzU32::MHz(72))
+        .freeze(&mut flash.acr);
+
+    let mut delay = SharedDelay::new(cp.SYST.delay(&clocks));
+    clock::start(dp.TIM2, &clocks);
+
+    let mut settings_store = SettingsStore::new(Stm32SettingsFlash::new(&mut flash));
+    let settings = match settings_store.load() {
+        Ok(Some(settings)) => settings,
+        Ok(None) => DEFAULT_SETTINGS,
+        Err(error) => {
+            rprintln!("Failed to load settings: {:?}", error);
+            DEFAULT_SETTINGS
+        }
+    };
+    rprintln!("{:?}", settings);
+
+    // Left, right, middle, back, forward.
+    let mut button_driver = ButtonDriver::new(
+        [
+            gpioc.pc3.into_pull_up_input(&mut gpioc.crl).erase(),
+            gpioc.pc4.into_pull_up_input(&mut gpioc.crl).erase(),
+            gpioc.pc5.into_pull_up_input(&mut gpioc.crl).erase(),
+            gpioc.pc6.into_pull_up_input(&mut gpioc.crl).erase(),
+            gpioc.pc7.into_pull_up_input(&mut gpioc.crl).erase(),
+        ],
+        BUTTON_DEBOUNCE,
+    );
+    WheelDriver::install(
+        gpiob.pb6.into_pull_up_input(&mut gpiob.crl),
+        gpiob.pb7.into_pull_up_input(&mut gpiob.crl),
+        WHEEL_STEPS_PER_DETENT,
+        &mut afio,
+        &mut exti,
+    );
+
+    motion_pin::install(
+        gpioa.pa3.into_pull_up_input(&mut gpioa.crl),
+        &mut afio,
+        &mut exti,
+    );
+
+    let pmw_spi = Spi::spi1(
+        dp.SPI1,
+        (
+            gpioa.pa5.into_alternate_push_pull(&mut gpioa.crl),
+            gpioa.pa6.into_floating_input(&mut gpioa.crl),
+            gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl),
+        ),
+        &mut afio.mapr,
+        Mode {
+            polarity: Polarity::IdleHigh,
+            phase: Phase::CaptureOnSecondTransition,
+        },
+        // The datasheet allows up to 2 MHz; SPI1 divides 72 MHz down to 1.125 MHz, fast enough
+        // for a motion burst in every 1 ms polling interval.
+        HertzU32::MHz(1),
+        clocks,
+    );
+
+    let mut pmw_driver = PmwDriver::new(
+        PmwSpiDevice::new(pmw_spi, delay.clone()),
+        PmwChipEnable::new(gpioa.pa4.into_push_pull_output(&mut gpioa.crl)),
+        delay.clone(),
+    );
+    // Keep retrying instead of panicking, so a loose sensor connection can be fixed without a
+    // power cycle.
+    let sensor_info = loop {
+        match pmw_driver.init() {
+            Ok(sensor_info) => break sensor_info,
+            Err(error) => {
+                rprintln!("Sensor init failed: {:?}", error);
+                delay.delay_ms(SENSOR_RETRY_DELAY_MS);
+            }
+        }
+    };
+    rprintln!("{:?}", sensor_info);
+
+    let mut mouse: Mouse<_, _, _, _, 5> =
+        Mouse::new(pmw_driver, sensor_info, settings_store, settings);
+    if let Err(error) = mouse.configure_sensor() {
+        rprintln!("Failed to configure the sensor: {:?}", error);
+    }
+    let mut config_server = ConfigServer::new(FIRMWARE_VERSION);
+
+    let usb_dm = gpioa.pa11.into_push_pull_output(&mut gpioa.crh);
+    let mut usb_dp = gpioa.pa12.into_push_pull_output(&mut gpioa.crh);
+    usb_dp.set_low();
+    delay.delay_ms(10);
+
+    let usb_peripheral = usb::Peripheral {
+        usb: dp.USB,
+        pin_dm: usb_dm.into_floating_input(&mut gpioa.crh),
+        pin_dp: usb_dp.into_floating_input(&mut gpioa.crh),
+    };
+
+    // Changing the polling rate at runtime would need a new enumeration, it's applied at boot.
+    let polling_rate = mouse.settings().polling_rate;
+    let mut usb_driver = UsbDriver::new(usb_peripheral, polling_rate);
+    let mut motion_accumulator = MotionAccumulator::new();
+    let mut report_timer = ReportTimer::new(polling_rate, clock::now_ms());
+
+    loop {
+        usb_driver.poll();
+        if let Some(request) = usb_driver.read_config_request() {
+            let response = config_server.handle_report(&mut mouse, &request);
+            usb_driver.send_config_response(response);
+        }
+
+        // Read whenever MOTION is asserted, on whichever wakeup comes first, rather than waiting
+        // for the next report. No SPI traffic while the mouse is still.
+        if motion_pin::is_pending() {
+            match mouse.read_motion() {
+                Ok(motion_data) => motion_accumulator.add(&motion_data),
+                Err(error) => rprintln!("Motion read failed: {:?}", error),
+            }
+        }
+
+        // The debouncers need every tick, whatever the polling rate. CPI buttons act right away,
+        // the rest waits for the next report.
+        let button_data = button_driver.get_current_data(clock::now_ms());
+        let mapped_buttons = mouse.map_buttons(button_data);
+        if let Err(error) = mouse.step_cpi(mapped_buttons.cpi_steps) {
+            rprintln!("Failed to set CPI: {:?}", error);
+        }
+
+        // Once per polling interval: queue one report with everything accumulated since the
+        // last, which the host picks up at its next poll.
+        if report_timer.is_due(clock::now_ms()) {
+            motion_accumulator.add_wheel(wheel_driver::take_detents());
+            usb_driver.handle_data(&mut motion_accumulator, mapped_buttons.mouse);
+            usb_driver.handle_keys(mapped_buttons.keyboard, mapped_buttons.consumer);
+        }
+
+        // Sleep until the next interrupt: the 1 kHz tick, MOTION or the wheel. USB is polled
+        // rather than interrupt driven, the tick bounds its latency to a millisecond.
+        cortex_m::asm::wfi();
+    }
+}
+
+#[global_allocator]
+pub static ALLOCATOR: CortexMHeap = CortexMHeap::empty();
+
+#[alloc_error_handler]
+fn oom(_: Layout) -> ! {
+    panic!("Ran out of memory");
+}
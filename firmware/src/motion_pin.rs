@@ -0,0 +1,44 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::Mutex;
+use cortex_m::peripheral::NVIC;
+use stm32f1xx_hal::afio;
+use stm32f1xx_hal::gpio::{Edge, ExtiPin, Input, Pin, PullUp};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, EXTI};
+
+pub type MotionInput = Pin<'A', 3, Input<PullUp>>;
+
+// Shared with the EXTI3 handler, which only acknowledges the edge: its job is waking the core
+// from WFI.
+static MOTION_PIN: Mutex<RefCell<Option<MotionInput>>> = Mutex::new(RefCell::new(None));
+
+/// Configures the sensor's active low MOTION output on PA3 to raise EXTI3 when motion becomes
+/// pending.
+pub fn install(mut pin: MotionInput, afio: &mut afio::Parts, exti: &mut EXTI) {
+    pin.make_interrupt_source(afio);
+    pin.trigger_on_edge(exti, Edge::Falling);
+    pin.enable_interrupt(exti);
+
+    cortex_m::interrupt::free(|cs| MOTION_PIN.borrow(cs).replace(Some(pin)));
+    unsafe { NVIC::unmask(Interrupt::EXTI3) };
+}
+
+/// Whether the sensor has motion to read, always true if the pin isn't installed. Taken from the
+/// pin level rather than from edges, so motion arriving during a burst read isn't missed.
+pub fn is_pending() -> bool {
+    cortex_m::interrupt::free(|cs| {
+        MOTION_PIN
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .is_none_or(|pin| pin.is_low())
+    })
+}
+
+#[interrupt]
+fn EXTI3() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(pin) = MOTION_PIN.borrow(cs).borrow_mut().as_mut() {
+            pin.clear_interrupt_pending_bit();
+        }
+    });
+}
@@ -0,0 +1,129 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::convert::Infallible;
+use cortex_m::prelude::{_embedded_hal_blocking_spi_Transfer, _embedded_hal_blocking_spi_Write};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::{self, ErrorKind, Operation, SpiDevice};
+use fugit::MicrosDurationU32;
+use stm32f1xx_hal::device::SPI1;
+use stm32f1xx_hal::gpio::{Alternate, Output, Pin};
+use stm32f1xx_hal::spi::{Error, Spi, Spi1NoRemap};
+use stm32f1xx_hal::timer::SysDelay;
+
+pub type PmwCe = Pin<'A', 4, Output>;
+pub type PmwSck = Pin<'A', 5, Alternate>;
+pub type PmwMiso = Pin<'A', 6>;
+pub type PmwMosi = Pin<'A', 7, Alternate>;
+pub type PmwSpi = Spi<SPI1, Spi1NoRemap, (PmwSck, PmwMiso, PmwMosi), u8>;
+
+// stm32f1xx-hal 0.10 only implements the embedded-hal 0.2 traits, so these wrappers adapt the
+// board peripherals to the embedded-hal 1.0 traits `PmwDriver` is written against.
+
+#[derive(Clone)]
+pub struct SharedDelay(Rc<RefCell<SysDelay>>);
+
+impl SharedDelay {
+    pub fn new(delay: SysDelay) -> Self {
+        Self(Rc::new(RefCell::new(delay)))
+    }
+}
+
+impl DelayNs for SharedDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay_us(ns.div_ceil(1_000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.borrow_mut().delay(MicrosDurationU32::micros(us));
+    }
+}
+
+pub struct PmwChipEnable(PmwCe);
+
+impl PmwChipEnable {
+    pub fn new(pin: PmwCe) -> Self {
+        Self(pin)
+    }
+}
+
+impl digital::ErrorType for PmwChipEnable {
+    type Error = Infallible;
+}
+
+impl OutputPin for PmwChipEnable {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high();
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct PmwSpiError(Error);
+
+impl spi::Error for PmwSpiError {
+    fn kind(&self) -> ErrorKind {
+        match self.0 {
+            Error::Overrun => ErrorKind::Overrun,
+            Error::ModeFault => ErrorKind::ModeFault,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<Error> for PmwSpiError {
+    fn from(error: Error) -> Self {
+        Self(error)
+    }
+}
+
+/// SPI1 as an embedded-hal 1.0 `SpiDevice`. NCS is left to `PmwDriver`, which needs to hold it
+/// across the tSRAD delay.
+pub struct PmwSpiDevice {
+    spi: PmwSpi,
+    delay: SharedDelay,
+}
+
+impl PmwSpiDevice {
+    pub fn new(spi: PmwSpi, delay: SharedDelay) -> Self {
+        Self { spi, delay }
+    }
+}
+
+impl spi::ErrorType for PmwSpiDevice {
+    type Error = PmwSpiError;
+}
+
+impl SpiDevice for PmwSpiDevice {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(words) => {
+                    words.fill(0xff);
+                    self.spi.transfer(words)?;
+                }
+                Operation::Write(words) => self.spi.write(words)?,
+                Operation::Transfer(read, write) => {
+                    for index in 0..read.len().max(write.len()) {
+                        let mut word = [write.get(index).copied().unwrap_or(0xff)];
+                        self.spi.transfer(&mut word)?;
+                        if let Some(read_word) = read.get_mut(index) {
+                            *read_word = word[0];
+                        }
+                    }
+                }
+                Operation::TransferInPlace(words) => {
+                    self.spi.transfer(words)?;
+                }
+                Operation::DelayNs(ns) => self.delay.delay_ns(*ns),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use pmw3360_mouse::settings_store::SettingsFlash;
+use stm32f1xx_hal::flash::{self, FlashSize, FlashWriter, SectorSize};
+
+// The settings pages follow the FLASH region declared in memory.x, which leaves them out of the
+// program image.
+const SETTINGS_OFFSET: u32 = 504 * 1024;
+
+/// The last four 2 KiB pages of the STM32F103's 512K flash.
+pub struct Stm32SettingsFlash<'a> {
+    writer: FlashWriter<'a>,
+}
+
+impl<'a> Stm32SettingsFlash<'a> {
+    pub fn new(flash: &'a mut flash::Parts) -> Self {
+        Self {
+            writer: flash.writer(SectorSize::Sz2K, FlashSize::Sz512K),
+        }
+    }
+}
+
+impl SettingsFlash for Stm32SettingsFlash<'_> {
+    type Error = flash::Error;
+
+    const PAGE_SIZE: usize = 2048;
+    const PAGE_COUNT: usize = 4;
+
+    fn read(&mut self, offset: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        let data = self
+            .writer
+            .read(SETTINGS_OFFSET + offset as u32, bytes.len())?;
+        bytes.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.writer.write(SETTINGS_OFFSET + offset as u32, bytes)
+    }
+
+    fn erase_page(&mut self, page: usize) -> Result<(), Self::Error> {
+        self.writer
+            .page_erase(SETTINGS_OFFSET + (page * Self::PAGE_SIZE) as u32)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config_hid::ConfigHid;
+use core::ptr::addr_of_mut;
+use pmw3360_mouse::button_data::ButtonData;
+use pmw3360_mouse::consumer_report::ConsumerReport;
+use pmw3360_mouse::keyboard_report::{KeyboardReport, KeyboardState};
+use pmw3360_mouse::motion_accumulator::MotionAccumulator;
+use pmw3360_mouse::mouse_report::{MouseReport, MAX_REPORT_DELTA, MAX_REPORT_WHEEL};
+use pmw3360_mouse::polling_rate::PollingRate;
+use pmw3360_protocol::message::REPORT_SIZE;
+use stm32_usbd::UsbBus;
+use stm32f1xx_hal::usb;
+use usb_device::bus::UsbBusAllocator;
+use usb_device::prelude::*;
+use usbd_hid_device::{Hid, HidReport};
+
+// Keyboard, consumer control and configuration aren't latency critical, unlike the mouse, which
+// is polled at the configured rate.
+const POLL_TIME_MS: u8 = 5;
+
+static mut USB_BUS_ALLOCATOR: Option<UsbBusAllocator<UsbBus<usb::Peripheral>>> = None;
+
+pub struct UsbDriver<'a> {
+    usb_device: UsbDevice<'a, UsbBus<usb::Peripheral>>,
+    hid: Hid<'a, HidMouseReport, UsbBus<usb::Peripheral>>,
+    keyboard_hid: Hid<'a, HidKeyboardReport, UsbBus<usb::Peripheral>>,
+    consumer_hid: Hid<'a, HidConsumerReport, UsbBus<usb::Peripheral>>,
+    config_hid: ConfigHid<'a, UsbBus<usb::Peripheral>>,
+    // Response the IN endpoint didn't take yet.
+    pending_config_response: Option<[u8; REPORT_SIZE]>,
+    last_button_data: Option<ButtonData>,
+    last_keyboard_state: Option<KeyboardState>,
+    last_consumer_usage: Option<u16>,
+}
+
+impl<'a> UsbDriver<'a> {
+    /// The polling rate goes into the endpoint descriptor, so it's fixed until the next
+    /// enumeration.
+    pub fn new(usb_peripheral: usb::Peripheral, polling_rate: PollingRate) -> Self {
+        // `new` runs once, so this is the only reference ever taken to the allocator.
+        let usb_bus: &'static UsbBusAllocator<_> =
+            unsafe { (*addr_of_mut!(USB_BUS_ALLOCATOR)).insert(UsbBus::new(usb_peripheral)) };
+
+        // Mouse, keyboard, consumer control and configuration are separate HID interfaces of one
+        // composite device, each with its own report descriptor.
+        let hid = Hid::new(usb_bus, polling_rate.interval_ms());
+        let keyboard_hid = Hid::new(usb_bus, POLL_TIME_MS);
+        let consumer_hid = Hid::new(usb_bus, POLL_TIME_MS);
+        let config_hid = ConfigHid::new(usb_bus, POLL_TIME_MS);
+        let usb_device = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x16c0, 0x27dd))
+            .manufacturer("Fake company")
+            .product("IDK MOUSE")
+            .serial_number("rev 3")
+            .build();
+
+        Self {
+            hid,
+            keyboard_hid,
+            consumer_hid,
+            config_hid,
+            pending_config_response: None,
+            usb_device,
+            last_button_data: None,
+            last_keyboard_state: None,
+            last_consumer_usage: None,
+        }
+    }
+
+    pub fn poll(&mut self) {
+        self.usb_device.poll(&mut [
+            &mut self.hid,
+            &mut self.keyboard_hid,
+            &mut self.consumer_hid,
+            &mut self.config_hid,
+        ]);
+    }
+
+    /// Takes the next configuration request, once the response to the previous one is out.
+    pub fn read_config_request(&mut self) -> Option<[u8; REPORT_SIZE]> {
+        if let Some(response) = self.pending_config_response {
+            if self.config_hid.write_report(&response).is_err() {
+                return None;
+            }
+            self.pending_config_response = None;
+        }
+
+        self.config_hid.read_report()
+    }
+
+    /// Sends the response to a configuration request, or keeps it until the endpoint is free.
+    pub fn send_config_response(&mut self, response: [u8; REPORT_SIZE]) {
+        if self.config_hid.write_report(&response).is_err() {
+            self.pending_config_response = Some(response);
+        }
+    }
+
+    /// Sends one report with as much of the accumulated motion and wheel as fits, if there is
+    /// motion or a button change to report. Motion is only taken out of the accumulator once the
+    /// endpoint accepted the report.
+    pub fn handle_data(&mut self, accumulator: &mut MotionAccumulator, button_data: ButtonData) {
+        if accumulator.is_empty() && self.last_button_data == Some(button_data) {
+            return;
+        }
+
+        let motion_data = accumulator.pending(MAX_REPORT_DELTA);
+        let wheel = accumulator.pending_wheel(MAX_REPORT_WHEEL);
+        let report = HidMouseReport(MouseReport::new(motion_data, button_data, wheel));
+        if self.hid.send_report(&report).is_ok() {
+            accumulator.consume(&motion_data);
+            accumulator.consume_wheel(wheel);
+            self.last_button_data = Some(button_data);
+        }
+    }
+
+    /// Sends the keyboard and consumer reports when their content changed.
+    pub fn handle_keys(&mut self, keyboard_state: KeyboardState, consumer_usage: u16) {
+        if self.last_keyboard_state != Some(keyboard_state) {
+            let report = HidKeyboardReport(KeyboardReport::new(&keyboard_state));
+            if self.keyboard_hid.send_report(&report).is_ok() {
+                self.last_keyboard_state = Some(keyboard_state);
+            }
+        }
+
+        if self.last_consumer_usage != Some(consumer_usage) {
+            let report = HidConsumerReport(ConsumerReport::new(consumer_usage));
+            if self.consumer_hid.send_report(&report).is_ok() {
+                self.last_consumer_usage = Some(consumer_usage);
+            }
+        }
+    }
+}
+
+// The reports live in the board-independent crate, which doesn't depend on usbd-hid-device.
+struct HidMouseReport(MouseReport);
+
+impl AsRef<[u8]> for HidMouseReport {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl HidReport for HidMouseReport {
+    const DESCRIPTOR: &'static [u8] = MouseReport::DESCRIPTOR;
+}
+
+struct HidKeyboardReport(KeyboardReport);
+
+impl AsRef<[u8]> for HidKeyboardReport {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl HidReport for HidKeyboardReport {
+    const DESCRIPTOR: &'static [u8] = KeyboardReport::DESCRIPTOR;
+}
+
+struct HidConsumerReport(ConsumerReport);
+
+impl AsRef<[u8]> for HidConsumerReport {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl HidReport for HidConsumerReport {
+    const DESCRIPTOR: &'static [u8] = ConsumerReport::DESCRIPTOR;
+}
@@ -0,0 +1,76 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::Mutex;
+use cortex_m::peripheral::NVIC;
+use pmw3360_mouse::quadrature::{QuadratureDecoder, StepsPerDetent};
+use stm32f1xx_hal::afio;
+use stm32f1xx_hal::gpio::{Edge, ExtiPin, Input, Pin, PullUp};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, EXTI};
+
+pub type WheelA = Pin<'B', 6, Input<PullUp>>;
+pub type WheelB = Pin<'B', 7, Input<PullUp>>;
+
+// Owned by the EXTI9_5 handler once installed, so no edge is missed while the main loop is busy
+// with the sensor.
+static WHEEL_DRIVER: Mutex<RefCell<Option<WheelDriver>>> = Mutex::new(RefCell::new(None));
+
+/// Scroll wheel encoder on PB6/PB7, decoded on every edge of either channel.
+pub struct WheelDriver {
+    a: WheelA,
+    b: WheelB,
+    decoder: QuadratureDecoder,
+}
+
+impl WheelDriver {
+    /// Configures both pins as EXTI sources on either edge and hands the driver to the interrupt
+    /// handler. Detents are read with `take_detents`.
+    pub fn install(
+        mut a: WheelA,
+        mut b: WheelB,
+        steps_per_detent: StepsPerDetent,
+        afio: &mut afio::Parts,
+        exti: &mut EXTI,
+    ) {
+        enable_edge_interrupt(&mut a, afio, exti);
+        enable_edge_interrupt(&mut b, afio, exti);
+
+        let decoder = QuadratureDecoder::new(steps_per_detent, a.is_high(), b.is_high());
+        cortex_m::interrupt::free(|cs| {
+            WHEEL_DRIVER
+                .borrow(cs)
+                .replace(Some(Self { a, b, decoder }));
+        });
+        unsafe { NVIC::unmask(Interrupt::EXTI9_5) };
+    }
+
+    fn on_edge(&mut self) {
+        self.a.clear_interrupt_pending_bit();
+        self.b.clear_interrupt_pending_bit();
+        self.decoder.update(self.a.is_high(), self.b.is_high());
+    }
+}
+
+/// Returns the detents decoded since the last call, 0 if the wheel isn't installed.
+pub fn take_detents() -> i32 {
+    cortex_m::interrupt::free(|cs| {
+        WHEEL_DRIVER
+            .borrow(cs)
+            .borrow_mut()
+            .as_mut()
+            .map_or(0, |wheel_driver| wheel_driver.decoder.take_detents())
+    })
+}
+
+fn enable_edge_interrupt(pin: &mut impl ExtiPin, afio: &mut afio::Parts, exti: &mut EXTI) {
+    pin.make_interrupt_source(afio);
+    pin.trigger_on_edge(exti, Edge::RisingFalling);
+    pin.enable_interrupt(exti);
+}
+
+#[interrupt]
+fn EXTI9_5() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(wheel_driver) = WHEEL_DRIVER.borrow(cs).borrow_mut().as_mut() {
+            wheel_driver.on_edge();
+        }
+    });
+}
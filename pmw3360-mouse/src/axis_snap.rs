@@ -0,0 +1,57 @@
+use crate::fine_rotation::sin_cos_q14;
+use crate::motion_data::MotionData;
+
+/// Largest snapping threshold, in degrees. Beyond 45° every direction would be near an axis.
+pub const MAX_AXIS_SNAP_THRESHOLD: u8 = 44;
+
+/// Software angle snapping: frames moving within a threshold of the X or Y axis have their minor
+/// component dropped, so hand-drawn horizontal and vertical lines come out straight.
+///
+/// Works independently of the sensor's own Angle_Snap register, e.g. with a wider threshold or
+/// after the software fine rotation. Disabled by default.
+#[derive(Debug, Default)]
+pub struct AxisSnap {
+    threshold: Option<u8>,
+    // tan(threshold) in Q14.
+    slope: i32,
+}
+
+impl AxisSnap {
+    /// `threshold` is in degrees and is capped at `MAX_AXIS_SNAP_THRESHOLD`; `None` disables
+    /// snapping.
+    pub fn new(threshold: Option<u8>) -> Self {
+        let threshold = threshold.map(|threshold| threshold.min(MAX_AXIS_SNAP_THRESHOLD));
+        let slope = threshold.map_or(0, |threshold| {
+            let (sin, cos) = sin_cos_q14(threshold as i16 * 10);
+            (sin << 14) / cos
+        });
+
+        Self { threshold, slope }
+    }
+
+    pub fn threshold(&self) -> Option<u8> {
+        self.threshold
+    }
+
+    pub fn apply(&self, motion_data: MotionData) -> MotionData {
+        if self.threshold.is_none() {
+            return motion_data;
+        }
+
+        let x = (motion_data.delta_x as i32).abs();
+        let y = (motion_data.delta_y as i32).abs();
+        if y << 14 <= x * self.slope {
+            MotionData {
+                delta_y: 0,
+                ..motion_data
+            }
+        } else if x << 14 <= y * self.slope {
+            MotionData {
+                delta_x: 0,
+                ..motion_data
+            }
+        } else {
+            motion_data
+        }
+    }
+}
@@ -0,0 +1,47 @@
+/// Largest number of buttons a `MouseReport` carries.
+pub const MAX_BUTTONS: usize = 16;
+
+/// Buttons with a conventional meaning, by their index in `ButtonData` (HID button number - 1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left = 0,
+    Right = 1,
+    Middle = 2,
+    Back = 3,
+    Forward = 4,
+}
+
+/// Pressed state of up to `MAX_BUTTONS` buttons. Button `n` is reported as HID button `n + 1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ButtonData {
+    pressed: u16,
+}
+
+impl ButtonData {
+    pub const fn from_bits(pressed: u16) -> Self {
+        Self { pressed }
+    }
+
+    pub fn is_pressed(&self, button: usize) -> bool {
+        button < MAX_BUTTONS && self.pressed & (1 << button) != 0
+    }
+
+    pub fn set_pressed(&mut self, button: usize, pressed: bool) {
+        assert!(button < MAX_BUTTONS, "Button index out of range.");
+        if pressed {
+            self.pressed |= 1 << button;
+        } else {
+            self.pressed &= !(1 << button);
+        }
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.is_pressed(button as usize)
+    }
+}
+
+impl From<ButtonData> for u16 {
+    fn from(button_data: ButtonData) -> Self {
+        button_data.pressed
+    }
+}
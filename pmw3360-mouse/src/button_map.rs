@@ -0,0 +1,158 @@
+use crate::button_data::{ButtonData, MouseButton, MAX_BUTTONS};
+use crate::keyboard_report::{KeyboardState, MAX_KEYS};
+
+/// What a physical button does when pressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ButtonAction {
+    #[default]
+    Disabled,
+    /// Mouse button, by its index in `ButtonData`.
+    Mouse(u8),
+    /// Keyboard usage held together with the modifier bits of `KeyboardState`. A `key` of 0
+    /// sends only the modifiers.
+    Keyboard {
+        modifiers: u8,
+        key: u8,
+    },
+    /// Consumer control usage, such as `consumer_report::VOLUME_UP`.
+    Consumer(u16),
+    /// Steps to the next or previous CPI preset, once per press.
+    CpiUp,
+    CpiDown,
+}
+
+impl ButtonAction {
+    pub const ENCODED_SIZE: usize = 4;
+
+    /// Encoding used by settings records: a tag byte followed by the action's parameters.
+    pub fn to_bytes(self) -> [u8; Self::ENCODED_SIZE] {
+        match self {
+            ButtonAction::Disabled => [0, 0, 0, 0],
+            ButtonAction::Mouse(button) => [1, button, 0, 0],
+            ButtonAction::Keyboard { modifiers, key } => [2, modifiers, key, 0],
+            ButtonAction::Consumer(usage) => {
+                let [usage_l, usage_h] = usage.to_le_bytes();
+                [3, usage_l, usage_h, 0]
+            }
+            ButtonAction::CpiUp => [4, 0, 0, 0],
+            ButtonAction::CpiDown => [5, 0, 0, 0],
+        }
+    }
+
+    pub fn from_bytes(bytes: [u8; Self::ENCODED_SIZE]) -> Option<Self> {
+        let action = match bytes[0] {
+            0 => ButtonAction::Disabled,
+            1 if (bytes[1] as usize) < MAX_BUTTONS => ButtonAction::Mouse(bytes[1]),
+            2 => ButtonAction::Keyboard {
+                modifiers: bytes[1],
+                key: bytes[2],
+            },
+            3 => ButtonAction::Consumer(u16::from_le_bytes([bytes[1], bytes[2]])),
+            4 => ButtonAction::CpiUp,
+            5 => ButtonAction::CpiDown,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Everything the pressed buttons translate to, ready to be put into reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MappedButtons {
+    pub mouse: ButtonData,
+    pub keyboard: KeyboardState,
+    /// Consumer usage to report, 0 when none. Only one consumer key is reported at a time.
+    pub consumer: u16,
+    /// Net number of CPI preset steps requested since the last call.
+    pub cpi_steps: i8,
+}
+
+/// Assigns an action to each of `N` physical buttons.
+#[derive(Debug)]
+pub struct ButtonMap<const N: usize> {
+    actions: [ButtonAction; N],
+    previous: ButtonData,
+}
+
+impl<const N: usize> ButtonMap<N> {
+    pub const fn new(actions: [ButtonAction; N]) -> Self {
+        Self {
+            actions,
+            previous: ButtonData::from_bits(0),
+        }
+    }
+
+    /// Every button reports the mouse button of the same index.
+    pub const fn standard() -> Self {
+        let mut actions = [ButtonAction::Disabled; N];
+        let mut button = 0;
+        while button < N {
+            actions[button] = ButtonAction::Mouse(button as u8);
+            button += 1;
+        }
+        Self::new(actions)
+    }
+
+    /// Like `standard`, with the left and right buttons swapped.
+    pub const fn left_handed() -> Self {
+        let mut button_map = Self::standard();
+        if N > MouseButton::Right as usize {
+            button_map.actions[MouseButton::Left as usize] =
+                ButtonAction::Mouse(MouseButton::Right as u8);
+            button_map.actions[MouseButton::Right as usize] =
+                ButtonAction::Mouse(MouseButton::Left as u8);
+        }
+        button_map
+    }
+
+    pub fn actions(&self) -> &[ButtonAction; N] {
+        &self.actions
+    }
+
+    pub const fn into_actions(self) -> [ButtonAction; N] {
+        self.actions
+    }
+
+    pub fn set_action(&mut self, button: usize, action: ButtonAction) {
+        self.actions[button] = action;
+    }
+
+    /// Translates the physical button state. Keys beyond the six a keyboard report holds are
+    /// dropped.
+    pub fn apply(&mut self, button_data: ButtonData) -> MappedButtons {
+        let mut mapped = MappedButtons::default();
+        let mut key_count = 0;
+
+        for (button, action) in self.actions.iter().enumerate() {
+            if !button_data.is_pressed(button) {
+                continue;
+            }
+            let newly_pressed = !self.previous.is_pressed(button);
+
+            match *action {
+                ButtonAction::Disabled => {}
+                ButtonAction::Mouse(mouse_button) => {
+                    mapped.mouse.set_pressed(mouse_button as usize, true)
+                }
+                ButtonAction::Keyboard { modifiers, key } => {
+                    mapped.keyboard.modifiers |= modifiers;
+                    if key != 0 && key_count < MAX_KEYS && !mapped.keyboard.keys.contains(&key) {
+                        mapped.keyboard.keys[key_count] = key;
+                        key_count += 1;
+                    }
+                }
+                ButtonAction::Consumer(usage) => {
+                    if mapped.consumer == 0 {
+                        mapped.consumer = usage;
+                    }
+                }
+                ButtonAction::CpiUp if newly_pressed => mapped.cpi_steps += 1,
+                ButtonAction::CpiDown if newly_pressed => mapped.cpi_steps -= 1,
+                ButtonAction::CpiUp | ButtonAction::CpiDown => {}
+            }
+        }
+
+        self.previous = button_data;
+        mapped
+    }
+}
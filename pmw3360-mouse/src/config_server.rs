@@ -0,0 +1,254 @@
+//! The mouse side of the configuration protocol in `pmw3360_protocol`.
+
+use crate::axis_snap::MAX_AXIS_SNAP_THRESHOLD;
+use crate::button_map::ButtonAction;
+use crate::fine_rotation::MAX_ANGLE_CORRECTION;
+use crate::mouse::Mouse;
+use crate::pmw_driver::PmwError;
+use crate::polling_rate::PollingRate;
+use crate::power_mode::PowerMode;
+use crate::registers::LiftHeight;
+use crate::sensor_image::SensorImage;
+use crate::settings::Settings;
+use crate::settings_store::SettingsFlash;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+use pmw3360_protocol::message::{
+    encode_error, AngleSnap, DeviceInfo, FrameChunk, LiftConfig, Reply, Request, Response, Status,
+    FRAME_CHUNK_SIZE, REPORT_SIZE,
+};
+
+/// Answers configuration requests on behalf of a `Mouse`.
+pub struct ConfigServer {
+    firmware_version: [u8; 3],
+    // Last captured image, read out in chunks.
+    frame: Option<SensorImage>,
+}
+
+impl ConfigServer {
+    pub fn new(firmware_version: [u8; 3]) -> Self {
+        Self {
+            firmware_version,
+            frame: None,
+        }
+    }
+
+    /// Decodes a request report and encodes the response to it. Reports that don't decode get
+    /// an error response echoing their command byte.
+    pub fn handle_report<SPI, CS, D, F, const N: usize>(
+        &mut self,
+        mouse: &mut Mouse<SPI, CS, D, F, N>,
+        report: &[u8],
+    ) -> [u8; REPORT_SIZE]
+    where
+        SPI: SpiDevice,
+        CS: OutputPin,
+        D: DelayNs,
+        F: SettingsFlash,
+    {
+        match Request::decode(report) {
+            Ok(request) => self.handle(mouse, request).encode(),
+            Err(error) => encode_error(report.get(1).copied().unwrap_or(0), error.into()),
+        }
+    }
+
+    pub fn handle<SPI, CS, D, F, const N: usize>(
+        &mut self,
+        mouse: &mut Mouse<SPI, CS, D, F, N>,
+        request: Request,
+    ) -> Response
+    where
+        SPI: SpiDevice,
+        CS: OutputPin,
+        D: DelayNs,
+        F: SettingsFlash,
+    {
+        Response {
+            command: request.command(),
+            result: self.reply(mouse, request),
+        }
+    }
+
+    fn reply<SPI, CS, D, F, const N: usize>(
+        &mut self,
+        mouse: &mut Mouse<SPI, CS, D, F, N>,
+        request: Request,
+    ) -> Result<Reply, Status>
+    where
+        SPI: SpiDevice,
+        CS: OutputPin,
+        D: DelayNs,
+        F: SettingsFlash,
+    {
+        let settings = *mouse.settings();
+        let reply = match request {
+            Request::GetDeviceInfo => {
+                let sensor_info = mouse.sensor_info();
+                Reply::DeviceInfo(DeviceInfo {
+                    firmware_version: self.firmware_version,
+                    product_id: sensor_info.product_id,
+                    revision_id: sensor_info.revision_id,
+                    srom_id: sensor_info.srom_id,
+                    button_count: N as u8,
+                })
+            }
+            Request::GetCpi => Reply::Cpi(settings.cpi),
+            Request::SetCpi(cpi) => update(mouse, Settings { cpi, ..settings })?,
+            Request::GetOrientation => Reply::Orientation(settings.orientation.into()),
+            Request::SetOrientation(orientation) => update(
+                mouse,
+                Settings {
+                    orientation: orientation.into(),
+                    ..settings
+                },
+            )?,
+            Request::GetPollingRate => Reply::PollingRate(settings.polling_rate.into()),
+            Request::SetPollingRate(interval_ms) => update(
+                mouse,
+                Settings {
+                    polling_rate: PollingRate::try_from(interval_ms)
+                        .map_err(|_| Status::InvalidArgument)?,
+                    ..settings
+                },
+            )?,
+            Request::GetLiftConfig => Reply::LiftConfig(LiftConfig {
+                lift_height: settings.lift_height.into(),
+                squal_threshold: settings.lift_squal_threshold,
+            }),
+            Request::SetLiftConfig(lift_config) => update(
+                mouse,
+                Settings {
+                    lift_height: LiftHeight::try_from(lift_config.lift_height)
+                        .map_err(|_| Status::InvalidArgument)?,
+                    lift_squal_threshold: lift_config.squal_threshold,
+                    ..settings
+                },
+            )?,
+            Request::GetButtonAction(button) => {
+                let action = settings.button_actions[button_index::<N>(button)?];
+                Reply::ButtonAction(button, action.to_bytes())
+            }
+            Request::SetButtonAction(button, action) => {
+                let mut button_actions = settings.button_actions;
+                button_actions[button_index::<N>(button)?] =
+                    ButtonAction::from_bytes(action).ok_or(Status::InvalidArgument)?;
+                update(
+                    mouse,
+                    Settings {
+                        button_actions,
+                        ..settings
+                    },
+                )?
+            }
+            Request::GetAngleCorrection => Reply::AngleCorrection(settings.angle_correction),
+            Request::SetAngleCorrection(angle_correction) => {
+                if !(-MAX_ANGLE_CORRECTION..=MAX_ANGLE_CORRECTION).contains(&angle_correction) {
+                    return Err(Status::InvalidArgument);
+                }
+                update(
+                    mouse,
+                    Settings {
+                        angle_correction,
+                        ..settings
+                    },
+                )?
+            }
+            Request::GetAngleSnap => Reply::AngleSnap(AngleSnap {
+                sensor: settings.sensor_angle_snap,
+                axis_threshold: settings.axis_snap_threshold,
+            }),
+            Request::SetAngleSnap(angle_snap) => {
+                if angle_snap.axis_threshold > Some(MAX_AXIS_SNAP_THRESHOLD) {
+                    return Err(Status::InvalidArgument);
+                }
+                update(
+                    mouse,
+                    Settings {
+                        sensor_angle_snap: angle_snap.sensor,
+                        axis_snap_threshold: angle_snap.axis_threshold,
+                        ..settings
+                    },
+                )?
+            }
+            Request::GetPowerMode => Reply::PowerMode(settings.power_mode.to_bytes()),
+            Request::SetPowerMode(power_mode) => {
+                // Config2 REST_EN, a flag.
+                if power_mode[0] > 1 {
+                    return Err(Status::InvalidArgument);
+                }
+                update(
+                    mouse,
+                    Settings {
+                        power_mode: PowerMode::from_bytes(&power_mode),
+                        ..settings
+                    },
+                )?
+            }
+            Request::SaveSettings => {
+                mouse.save_settings().map_err(|_| Status::FlashError)?;
+                Reply::Done
+            }
+            Request::ReadRegister(address) => {
+                let value = mouse
+                    .read_register(address)
+                    .ok_or(Status::InvalidArgument)?
+                    .map_err(sensor_status)?;
+                Reply::Register { address, value }
+            }
+            Request::CaptureFrame => {
+                self.frame = None;
+                self.frame = Some(mouse.capture_frame().map_err(sensor_status)?);
+                Reply::Done
+            }
+            Request::ReadFrame { offset } => {
+                let frame = self.frame.as_ref().ok_or(Status::NoFrame)?;
+                let pixels = frame
+                    .pixels()
+                    .get(offset as usize..)
+                    .filter(|pixels| !pixels.is_empty())
+                    .ok_or(Status::InvalidArgument)?;
+                let pixels = &pixels[..pixels.len().min(FRAME_CHUNK_SIZE)];
+
+                let mut chunk = FrameChunk {
+                    offset,
+                    len: pixels.len() as u8,
+                    pixels: [0; FRAME_CHUNK_SIZE],
+                };
+                chunk.pixels[..pixels.len()].copy_from_slice(pixels);
+                Reply::FrameChunk(chunk)
+            }
+        };
+        Ok(reply)
+    }
+}
+
+fn update<SPI, CS, D, F, const N: usize>(
+    mouse: &mut Mouse<SPI, CS, D, F, N>,
+    settings: Settings,
+) -> Result<Reply, Status>
+where
+    SPI: SpiDevice,
+    CS: OutputPin,
+    D: DelayNs,
+    F: SettingsFlash,
+{
+    mouse.set_settings(settings).map_err(sensor_status)?;
+    Ok(Reply::Done)
+}
+
+fn button_index<const N: usize>(button: u8) -> Result<usize, Status> {
+    let button = button as usize;
+    if button < N {
+        Ok(button)
+    } else {
+        Err(Status::InvalidArgument)
+    }
+}
+
+fn sensor_status(error: PmwError) -> Status {
+    match error {
+        PmwError::UnsupportedCpi(_) | PmwError::UnsupportedAngle(_) => Status::InvalidArgument,
+        _ => Status::SensorError,
+    }
+}
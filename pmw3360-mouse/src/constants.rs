@@ -1,20 +1,26 @@
 use fugit::MicrosDurationU32;
 
-pub const REG_MOTION: u8 = 0x02;
-pub const REG_DELTA_X_L: u8 = 0x03;
-pub const REG_DELTA_X_H: u8 = 0x04;
-pub const REG_DELTA_Y_L: u8 = 0x05;
-pub const REG_DELTA_Y_H: u8 = 0x06;
-pub const REG_CONFIG_2: u8 = 0x10;
-pub const REG_SROM_ENABLE: u8 = 0x13;
-pub const REG_MOTION_BURST: u8 = 0x50;
-pub const REG_SROM_LOAD_BURST: u8 = 0x62;
-pub const REG_POWER_UP_RESET: u8 = 0x3a;
+pub const MIN_CPI: u16 = 100;
+pub const MAX_CPI: u16 = 12000;
+pub const CPI_STEP: u16 = 100;
 
 pub const READ_ADDRESS_DATA_DELAY: MicrosDurationU32 = MicrosDurationU32::micros(160);
+// tSWW / tSWR
+pub const WRITE_COMMAND_DELAY: MicrosDurationU32 = MicrosDurationU32::micros(180);
+// tSRW / tSRR
+pub const READ_COMMAND_DELAY: MicrosDurationU32 = MicrosDurationU32::micros(20);
 pub const INIT_DELAY: MicrosDurationU32 = MicrosDurationU32::millis(50);
 pub const SROM_ENABLE_DELAY: MicrosDurationU32 = MicrosDurationU32::millis(10);
 pub const SROM_DOWNLOAD_DELAY: MicrosDurationU32 = MicrosDurationU32::millis(1);
+pub const SROM_BYTE_DELAY: MicrosDurationU32 = MicrosDurationU32::micros(15);
+pub const SROM_CRC_DELAY: MicrosDurationU32 = MicrosDurationU32::millis(10);
+pub const SROM_UPLOAD_ATTEMPTS: usize = 3;
+pub const FRAME_CAPTURE_DELAY: MicrosDurationU32 = MicrosDurationU32::millis(20);
+
+// SROM_ID reported while PMW_3360_FIRMWARE is running, and the Data_Out value of a passing SROM
+// CRC self-test.
+pub const PMW_3360_FIRMWARE_ID: u8 = 0x04;
+pub const SROM_CRC_PASS: u16 = 0xbeef;
 
 pub const PMW_3360_FIRMWARE: [u8; 4094] = [
     0x01, 0x04, 0x8e, 0x96, 0x6e, 0x77, 0x3e, 0xfe, 0x7e, 0x5f, 0x1d, 0xb8, 0xf2, 0x66, 0x4e, 0xff,
@@ -0,0 +1,42 @@
+// Consumer control usages for common media keys.
+pub const PLAY_PAUSE: u16 = 0xcd;
+pub const NEXT_TRACK: u16 = 0xb5;
+pub const PREVIOUS_TRACK: u16 = 0xb6;
+pub const MUTE: u16 = 0xe2;
+pub const VOLUME_UP: u16 = 0xe9;
+pub const VOLUME_DOWN: u16 = 0xea;
+
+/// Consumer control report carrying one usage, such as a media key.
+pub struct ConsumerReport {
+    // Bytes usage:
+    // bytes 0..1: consumer usage, little endian, 0 when no key is pressed
+    bytes: [u8; 2],
+}
+
+impl ConsumerReport {
+    pub const DESCRIPTOR: &'static [u8] = &[
+        0x05, 0x0c, // USAGE_PAGE Consumer
+        0x09, 0x01, // USAGE Consumer Control
+        0xa1, 0x01, // COLLECTION Application
+        0x19, 0x00, // USAGE_MINIMUM 0
+        0x2a, 0xff, 0x03, // USAGE_MAXIMUM 0x3ff
+        0x15, 0x00, // LOGICAL_MINIMUM 0
+        0x26, 0xff, 0x03, // LOGICAL_MAXIMUM 0x3ff
+        0x75, 0x10, // REPORT_SIZE 16
+        0x95, 0x01, // REPORT_COUNT 1
+        0x81, 0x00, // INPUT Data,Ary,Abs
+        0xc0, // END COLLECTION
+    ];
+
+    pub fn new(usage: u16) -> Self {
+        Self {
+            bytes: usage.to_le_bytes(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for ConsumerReport {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
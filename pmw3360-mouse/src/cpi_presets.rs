@@ -0,0 +1,44 @@
+pub const DEFAULT_CPI_PRESETS: [u16; 4] = [400, 800, 1600, 3200];
+
+/// A fixed list of CPI values, in ascending order, the mouse can step through at runtime.
+///
+/// Steps are taken relative to a current CPI rather than a stored position, so a CPI set some
+/// other way, e.g. by the host, steps to its neighbouring presets.
+#[derive(Debug)]
+pub struct CpiPresets<const N: usize> {
+    presets: [u16; N],
+}
+
+impl<const N: usize> CpiPresets<N> {
+    pub fn new(presets: [u16; N]) -> Self {
+        assert!(N > 0, "No CPI presets.");
+        assert!(
+            presets.is_sorted_by(|a, b| a < b),
+            "CPI presets must be in ascending order."
+        );
+        Self { presets }
+    }
+
+    /// The lowest preset above `cpi`, wrapping around to the first one.
+    pub fn next(&self, cpi: u16) -> u16 {
+        self.presets
+            .into_iter()
+            .find(|&preset| preset > cpi)
+            .unwrap_or(self.presets[0])
+    }
+
+    /// The highest preset below `cpi`, wrapping around to the last one.
+    pub fn previous(&self, cpi: u16) -> u16 {
+        self.presets
+            .into_iter()
+            .rev()
+            .find(|&preset| preset < cpi)
+            .unwrap_or(self.presets[N - 1])
+    }
+}
+
+impl Default for CpiPresets<4> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CPI_PRESETS)
+    }
+}
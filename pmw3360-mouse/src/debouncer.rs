@@ -0,0 +1,93 @@
+use crate::button_data::ButtonData;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DebounceAlgorithm {
+    /// Reports a change as soon as it's seen, then ignores the input for the debounce time. No
+    /// added latency, but a noise spike on an idle line becomes a click.
+    #[default]
+    Eager,
+    /// Reports a change once the input held the new level for the whole debounce time.
+    Deferred,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DebounceConfig {
+    pub algorithm: DebounceAlgorithm,
+    pub debounce_ms: u16,
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: DebounceAlgorithm::Eager,
+            debounce_ms: 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ButtonState {
+    pressed: bool,
+    // Eager: when the last change was reported. Deferred: since when the input differs from
+    // `pressed`.
+    since_ms: Option<u32>,
+}
+
+/// Debounces `N` buttons, each with its own algorithm and debounce time.
+///
+/// Timestamps are milliseconds from any free-running clock; wrapping around is fine.
+#[derive(Debug)]
+pub struct Debouncer<const N: usize> {
+    configs: [DebounceConfig; N],
+    states: [ButtonState; N],
+}
+
+impl<const N: usize> Debouncer<N> {
+    pub fn new(configs: [DebounceConfig; N]) -> Self {
+        Self {
+            configs,
+            states: [ButtonState::default(); N],
+        }
+    }
+
+    pub fn config(&self, button: usize) -> DebounceConfig {
+        self.configs[button]
+    }
+
+    pub fn set_config(&mut self, button: usize, config: DebounceConfig) {
+        self.configs[button] = config;
+        self.states[button].since_ms = None;
+    }
+
+    /// Feeds the raw button levels sampled at `now_ms` and returns the debounced state.
+    pub fn update(&mut self, raw: ButtonData, now_ms: u32) -> ButtonData {
+        let mut debounced = ButtonData::default();
+        for (button, (config, state)) in self.configs.iter().zip(&mut self.states).enumerate() {
+            let pressed = raw.is_pressed(button);
+            let elapsed =
+                |since_ms: u32| now_ms.wrapping_sub(since_ms) >= config.debounce_ms as u32;
+
+            match config.algorithm {
+                DebounceAlgorithm::Eager => {
+                    if pressed != state.pressed && state.since_ms.is_none_or(elapsed) {
+                        state.pressed = pressed;
+                        state.since_ms = Some(now_ms);
+                    }
+                }
+                DebounceAlgorithm::Deferred => {
+                    if pressed == state.pressed {
+                        state.since_ms = None;
+                    } else {
+                        let since_ms = *state.since_ms.get_or_insert(now_ms);
+                        if elapsed(since_ms) {
+                            state.pressed = pressed;
+                            state.since_ms = None;
+                        }
+                    }
+                }
+            }
+            debounced.set_pressed(button, state.pressed);
+        }
+        debounced
+    }
+}
@@ -0,0 +1,106 @@
+use crate::motion_data::MotionData;
+
+/// Largest correction the sensor's Angle_Tune register accepts, in degrees.
+pub const MAX_ANGLE_TUNE: i8 = 30;
+
+/// Largest mounting angle correction, in tenths of a degree. Half a turn either way covers every
+/// mounting angle.
+pub const MAX_ANGLE_CORRECTION: i16 = 1800;
+
+const FRACTION_BITS: u32 = 14;
+const ONE: i32 = 1 << FRACTION_BITS;
+
+/// Software rotation by an arbitrary angle, for mounting corrections beyond Angle_Tune's ±30°.
+///
+/// Angles are in tenths of a degree and rotate in the same direction as positive Angle_Tune
+/// values. The rotation runs in Q14 fixed point; the fractional counts left over after rounding
+/// are carried into the next frame, so slow motion isn't lost and the total distance stays exact.
+#[derive(Debug)]
+pub struct FineRotation {
+    decidegrees: i16,
+    sin: i32,
+    cos: i32,
+    remainder_x: i32,
+    remainder_y: i32,
+}
+
+impl FineRotation {
+    pub fn new(decidegrees: i16) -> Self {
+        let (sin, cos) = sin_cos_q14(decidegrees);
+        Self {
+            decidegrees,
+            sin,
+            cos,
+            remainder_x: 0,
+            remainder_y: 0,
+        }
+    }
+
+    pub fn decidegrees(&self) -> i16 {
+        self.decidegrees
+    }
+
+    pub fn apply(&mut self, motion_data: MotionData) -> MotionData {
+        if self.decidegrees == 0 {
+            return motion_data;
+        }
+
+        let x = motion_data.delta_x as i32;
+        let y = motion_data.delta_y as i32;
+        let rotated_x = x * self.cos + y * self.sin + self.remainder_x;
+        let rotated_y = y * self.cos - x * self.sin + self.remainder_y;
+
+        let delta_x = (rotated_x >> FRACTION_BITS).clamp(i16::MIN as i32, i16::MAX as i32);
+        let delta_y = (rotated_y >> FRACTION_BITS).clamp(i16::MIN as i32, i16::MAX as i32);
+        self.remainder_x = rotated_x - (delta_x << FRACTION_BITS);
+        self.remainder_y = rotated_y - (delta_y << FRACTION_BITS);
+
+        MotionData {
+            delta_x: delta_x as i16,
+            delta_y: delta_y as i16,
+            ..motion_data
+        }
+    }
+}
+
+impl Default for FineRotation {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Splits an angle correction in tenths of a degree into the whole degrees the sensor's
+/// Angle_Tune can take and the rest, which is left to `FineRotation`.
+pub fn split_angle_correction(decidegrees: i16) -> (i8, i16) {
+    let angle_tune = (decidegrees / 10).clamp(-MAX_ANGLE_TUNE as i16, MAX_ANGLE_TUNE as i16);
+    (angle_tune as i8, decidegrees - angle_tune * 10)
+}
+
+pub(crate) fn sin_cos_q14(decidegrees: i16) -> (i32, i32) {
+    // Reduce to ±45° around the nearest quarter turn, where the series below converge quickly.
+    let decidegrees = decidegrees as i32;
+    let quadrant = (decidegrees + 450).div_euclid(900);
+    let remainder = decidegrees - quadrant * 900;
+
+    let r = remainder as f32 * (core::f32::consts::PI / 1800.0);
+    let r2 = r * r;
+    let sin = r * (1.0 - r2 / 6.0 * (1.0 - r2 / 20.0 * (1.0 - r2 / 42.0)));
+    let cos = 1.0 - r2 / 2.0 * (1.0 - r2 / 12.0 * (1.0 - r2 / 30.0 * (1.0 - r2 / 56.0)));
+    let (sin, cos) = (to_q14(sin), to_q14(cos));
+
+    match quadrant.rem_euclid(4) {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    }
+}
+
+fn to_q14(value: f32) -> i32 {
+    let scaled = value * ONE as f32;
+    if scaled >= 0.0 {
+        (scaled + 0.5) as i32
+    } else {
+        (scaled - 0.5) as i32
+    }
+}
@@ -0,0 +1,69 @@
+/// Number of simultaneously pressed keys a `KeyboardReport` can carry.
+pub const MAX_KEYS: usize = 6;
+
+/// Modifier bits of `KeyboardState::modifiers`.
+pub mod modifiers {
+    pub const LEFT_CTRL: u8 = 1 << 0;
+    pub const LEFT_SHIFT: u8 = 1 << 1;
+    pub const LEFT_ALT: u8 = 1 << 2;
+    pub const LEFT_GUI: u8 = 1 << 3;
+    pub const RIGHT_CTRL: u8 = 1 << 4;
+    pub const RIGHT_SHIFT: u8 = 1 << 5;
+    pub const RIGHT_ALT: u8 = 1 << 6;
+    pub const RIGHT_GUI: u8 = 1 << 7;
+}
+
+/// Pressed modifiers and keyboard usages, unused key slots set to 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardState {
+    pub modifiers: u8,
+    pub keys: [u8; MAX_KEYS],
+}
+
+pub struct KeyboardReport {
+    // Bytes usage:
+    // byte 0: modifiers
+    // byte 1: reserved
+    // bytes 2..7: key usages
+    bytes: [u8; 8],
+}
+
+impl KeyboardReport {
+    pub const DESCRIPTOR: &'static [u8] = &[
+        0x05, 0x01, // USAGE_PAGE Generic Desktop
+        0x09, 0x06, // USAGE Keyboard
+        0xa1, 0x01, // COLLECTION Application
+        0x05, 0x07, // USAGE_PAGE Keyboard
+        0x19, 0xe0, // USAGE_MINIMUM Left Control
+        0x29, 0xe7, // USAGE_MAXIMUM Right GUI
+        0x15, 0x00, // LOGICAL_MINIMUM 0
+        0x25, 0x01, // LOGICAL_MAXIMUM 1
+        0x75, 0x01, // REPORT_SIZE 1
+        0x95, 0x08, // REPORT_COUNT 8
+        0x81, 0x02, // INPUT Data,Var,Abs
+        0x75, 0x08, // REPORT_SIZE 8
+        0x95, 0x01, // REPORT_COUNT 1
+        0x81, 0x01, // INPUT Cnst,Ary,Abs
+        0x19, 0x00, // USAGE_MINIMUM 0
+        0x29, 0xff, // USAGE_MAXIMUM 255
+        0x15, 0x00, // LOGICAL_MINIMUM 0
+        0x26, 0xff, 0x00, // LOGICAL_MAXIMUM 255
+        0x75, 0x08, // REPORT_SIZE 8
+        0x95, 0x06, // REPORT_COUNT 6
+        0x81, 0x00, // INPUT Data,Ary,Abs
+        0xc0, // END COLLECTION
+    ];
+
+    pub fn new(keyboard_state: &KeyboardState) -> Self {
+        let mut bytes = [0; 8];
+        bytes[0] = keyboard_state.modifiers;
+        bytes[2..].copy_from_slice(&keyboard_state.keys);
+        Self { bytes }
+    }
+}
+
+impl AsRef<[u8]> for KeyboardReport {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
@@ -0,0 +1,38 @@
+//! Board-independent parts of the PMW3360 mouse firmware: the sensor driver, motion decoding and
+//! HID report encoding. Builds for `no_std` targets; enable the `std` feature to use it from host
+//! code and tests, which also brings in a simulated sensor in [`sim`] and simulated flash in
+//! [`sim_flash`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod axis_snap;
+pub mod button_data;
+pub mod button_map;
+pub mod config_server;
+pub mod constants;
+pub mod consumer_report;
+pub mod cpi_presets;
+pub mod debouncer;
+pub mod fine_rotation;
+pub mod keyboard_report;
+pub mod lift_filter;
+pub mod motion_accumulator;
+pub mod motion_data;
+pub mod motion_pipeline;
+pub mod mouse;
+pub mod mouse_report;
+pub mod orientation;
+pub mod pmw_driver;
+pub mod polling_rate;
+pub mod power_mode;
+pub mod quadrature;
+pub mod registers;
+pub mod sensor_image;
+pub mod settings;
+pub mod settings_store;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod sim_flash;
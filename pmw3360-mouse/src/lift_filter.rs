@@ -0,0 +1,44 @@
+use crate::motion_data::MotionData;
+
+/// Drops motion while the mouse is lifted, so repositioning it doesn't move the cursor.
+///
+/// The sensor's own Lift_Stat bit is always honoured. On top of that an optional SQUAL
+/// threshold treats frames with too few surface features as lifted, which cuts off earlier than
+/// the sensor's 2 mm/3 mm lift-off distance.
+#[derive(Debug, Default)]
+pub struct LiftFilter {
+    squal_threshold: Option<u8>,
+}
+
+impl LiftFilter {
+    pub fn new(squal_threshold: Option<u8>) -> Self {
+        Self { squal_threshold }
+    }
+
+    pub fn squal_threshold(&self) -> Option<u8> {
+        self.squal_threshold
+    }
+
+    pub fn set_squal_threshold(&mut self, squal_threshold: Option<u8>) {
+        self.squal_threshold = squal_threshold;
+    }
+
+    pub fn is_lifted(&self, motion_data: &MotionData) -> bool {
+        motion_data.lifted()
+            || self
+                .squal_threshold
+                .is_some_and(|threshold| motion_data.squal < threshold)
+    }
+
+    pub fn apply(&self, motion_data: MotionData) -> MotionData {
+        if self.is_lifted(&motion_data) {
+            MotionData {
+                delta_x: 0,
+                delta_y: 0,
+                ..motion_data
+            }
+        } else {
+            motion_data
+        }
+    }
+}
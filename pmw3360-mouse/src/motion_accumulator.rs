@@ -0,0 +1,55 @@
+use crate::motion_data::MotionData;
+
+/// Sums motion and wheel detents between the input loop and USB so counts read while the endpoint
+/// is busy, or beyond what one report can carry, are sent later instead of dropped.
+#[derive(Debug, Default)]
+pub struct MotionAccumulator {
+    delta_x: i32,
+    delta_y: i32,
+    wheel: i32,
+}
+
+impl MotionAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, motion_data: &MotionData) {
+        self.delta_x = self.delta_x.saturating_add(motion_data.delta_x as i32);
+        self.delta_y = self.delta_y.saturating_add(motion_data.delta_y as i32);
+    }
+
+    pub fn add_wheel(&mut self, detents: i32) {
+        self.wheel = self.wheel.saturating_add(detents);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delta_x == 0 && self.delta_y == 0 && self.wheel == 0
+    }
+
+    /// The part of the accumulated motion that fits into one report with deltas limited to
+    /// `±max`. Nothing is removed until the report was sent and `consume` is called.
+    pub fn pending(&self, max: i16) -> MotionData {
+        let max = max as i32;
+        MotionData {
+            delta_x: self.delta_x.clamp(-max, max) as i16,
+            delta_y: self.delta_y.clamp(-max, max) as i16,
+            ..MotionData::default()
+        }
+    }
+
+    /// Like `pending`, for the wheel.
+    pub fn pending_wheel(&self, max: i8) -> i8 {
+        let max = max as i32;
+        self.wheel.clamp(-max, max) as i8
+    }
+
+    pub fn consume(&mut self, motion_data: &MotionData) {
+        self.delta_x -= motion_data.delta_x as i32;
+        self.delta_y -= motion_data.delta_y as i32;
+    }
+
+    pub fn consume_wheel(&mut self, wheel: i8) {
+        self.wheel -= wheel as i32;
+    }
+}
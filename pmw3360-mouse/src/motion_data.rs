@@ -0,0 +1,58 @@
+use crate::registers::{Motion, Observation, OpMode};
+use alloc::vec::Vec;
+
+/// One decoded Motion_Burst frame, with deltas in sensor coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MotionData {
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub motion: Motion,
+    pub observation: Observation,
+    /// Surface quality: roughly a quarter of the number of features the sensor tracks.
+    pub squal: u8,
+    /// Average raw pixel value of the frame, divided by 1024.
+    pub raw_data_sum: u8,
+    pub maximum_raw_data: u8,
+    pub minimum_raw_data: u8,
+    /// Exposure time in clock cycles.
+    pub shutter: u16,
+}
+
+impl MotionData {
+    pub fn lifted(&self) -> bool {
+        self.motion.lift_stat()
+    }
+
+    pub fn op_mode(&self) -> OpMode {
+        self.motion.op_mode()
+    }
+}
+
+impl From<Vec<u8>> for MotionData {
+    fn from(value: Vec<u8>) -> Self {
+        if value.len() != 12 {
+            panic!("Tried to decode invalid motion data.");
+        }
+
+        let motion = Motion(value[0]);
+        let (delta_x, delta_y) = if motion.mot() {
+            let delta_x = value[2] as i16 | ((value[3] as i16) << 8);
+            let delta_y = value[4] as i16 | ((value[5] as i16) << 8);
+            (delta_x, delta_y)
+        } else {
+            (0, 0)
+        };
+
+        Self {
+            delta_x,
+            delta_y,
+            motion,
+            observation: Observation(value[1]),
+            squal: value[6],
+            raw_data_sum: value[7],
+            maximum_raw_data: value[8],
+            minimum_raw_data: value[9],
+            shutter: u16::from_be_bytes([value[10], value[11]]),
+        }
+    }
+}
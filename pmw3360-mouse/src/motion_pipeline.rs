@@ -0,0 +1,67 @@
+use crate::axis_snap::AxisSnap;
+use crate::fine_rotation::FineRotation;
+use crate::lift_filter::LiftFilter;
+use crate::motion_data::MotionData;
+use crate::orientation::Orientation;
+
+/// Processing applied to every decoded sensor frame before it's accumulated into reports.
+#[derive(Debug, Default)]
+pub struct MotionPipeline {
+    lift_filter: LiftFilter,
+    fine_rotation: FineRotation,
+    orientation: Orientation,
+    axis_snap: AxisSnap,
+}
+
+impl MotionPipeline {
+    pub fn new(lift_filter: LiftFilter, orientation: Orientation) -> Self {
+        Self {
+            lift_filter,
+            fine_rotation: FineRotation::default(),
+            orientation,
+            axis_snap: AxisSnap::default(),
+        }
+    }
+
+    pub fn lift_filter(&self) -> &LiftFilter {
+        &self.lift_filter
+    }
+
+    pub fn lift_filter_mut(&mut self) -> &mut LiftFilter {
+        &mut self.lift_filter
+    }
+
+    /// Software part of the mounting angle correction, in tenths of a degree. It's applied in
+    /// sensor coordinates, like the sensor's own Angle_Tune.
+    pub fn fine_rotation(&self) -> i16 {
+        self.fine_rotation.decidegrees()
+    }
+
+    pub fn set_fine_rotation(&mut self, decidegrees: i16) {
+        self.fine_rotation = FineRotation::new(decidegrees);
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// Software angle snapping threshold in degrees, or `None` when snapping is off.
+    pub fn axis_snap(&self) -> Option<u8> {
+        self.axis_snap.threshold()
+    }
+
+    pub fn set_axis_snap(&mut self, threshold: Option<u8>) {
+        self.axis_snap = AxisSnap::new(threshold);
+    }
+
+    pub fn process(&mut self, motion_data: MotionData) -> MotionData {
+        let motion_data = self.lift_filter.apply(motion_data);
+        let motion_data = self.fine_rotation.apply(motion_data);
+        let motion_data = self.orientation.apply(motion_data);
+        self.axis_snap.apply(motion_data)
+    }
+}
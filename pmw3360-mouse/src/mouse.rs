@@ -0,0 +1,143 @@
+//! The board-independent state of a running mouse, so the firmware and host-side models of it
+//! apply settings the same way.
+
+use crate::button_data::ButtonData;
+use crate::button_map::{ButtonMap, MappedButtons};
+use crate::cpi_presets::CpiPresets;
+use crate::motion_data::MotionData;
+use crate::motion_pipeline::MotionPipeline;
+use crate::pmw_driver::{PmwDriver, PmwError, SensorInfo};
+use crate::registers::Register;
+use crate::sensor_image::SensorImage;
+use crate::settings::Settings;
+use crate::settings_store::{SettingsFlash, SettingsStore};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+
+/// An initialised sensor with `N` buttons, the processing configured from `Settings`, and the
+/// store the settings are saved to.
+pub struct Mouse<SPI, CS, D, F, const N: usize> {
+    pmw_driver: PmwDriver<SPI, CS, D>,
+    sensor_info: SensorInfo,
+    settings_store: SettingsStore<F>,
+    settings: Settings,
+    motion_pipeline: MotionPipeline,
+    button_map: ButtonMap<N>,
+    cpi_presets: CpiPresets<4>,
+}
+
+impl<SPI, CS, D, F, const N: usize> Mouse<SPI, CS, D, F, N>
+where
+    SPI: SpiDevice,
+    CS: OutputPin,
+    D: DelayNs,
+    F: SettingsFlash,
+{
+    /// Configures processing and buttons from `settings`. The sensor isn't touched, call
+    /// `configure_sensor` once before reading motion.
+    pub fn new(
+        pmw_driver: PmwDriver<SPI, CS, D>,
+        sensor_info: SensorInfo,
+        settings_store: SettingsStore<F>,
+        settings: Settings,
+    ) -> Self {
+        let mut motion_pipeline = MotionPipeline::default();
+        settings.configure_pipeline(&mut motion_pipeline);
+        Self {
+            pmw_driver,
+            sensor_info,
+            settings_store,
+            settings,
+            motion_pipeline,
+            button_map: settings.button_map(),
+            cpi_presets: CpiPresets::default(),
+        }
+    }
+
+    pub fn release(self) -> (PmwDriver<SPI, CS, D>, SettingsStore<F>) {
+        (self.pmw_driver, self.settings_store)
+    }
+
+    pub fn sensor_info(&self) -> SensorInfo {
+        self.sensor_info
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Writes the current settings to the sensor.
+    pub fn configure_sensor(&mut self) -> Result<(), PmwError> {
+        self.settings.configure_sensor(&mut self.pmw_driver)
+    }
+
+    /// Applies `settings` to the sensor, processing and buttons. If the sensor rejects them, the
+    /// previous settings stay in effect.
+    pub fn set_settings(&mut self, settings: Settings) -> Result<(), PmwError> {
+        if let Err(error) = settings.configure_sensor(&mut self.pmw_driver) {
+            // Best effort, the sensor may have taken part of the new settings.
+            let _ = self.configure_sensor();
+            return Err(error);
+        }
+
+        settings.configure_pipeline(&mut self.motion_pipeline);
+        self.button_map = settings.button_map();
+        self.settings = settings;
+        Ok(())
+    }
+
+    /// Saves the current settings, so they're loaded at the next boot.
+    pub fn save_settings(&mut self) -> Result<(), F::Error> {
+        self.settings_store.save(&self.settings)
+    }
+
+    /// Reads a frame and runs it through the motion pipeline.
+    pub fn read_motion(&mut self) -> Result<MotionData, PmwError> {
+        let motion_data = self.pmw_driver.read_motion()?;
+        Ok(self.motion_pipeline.process(motion_data))
+    }
+
+    pub fn map_buttons(&mut self, button_data: ButtonData) -> MappedButtons {
+        self.button_map.apply(button_data)
+    }
+
+    /// Moves through the CPI presets by `steps`, as requested by `MappedButtons::cpi_steps`,
+    /// starting from the current CPI. The new CPI becomes part of the settings, but isn't saved.
+    pub fn step_cpi(&mut self, steps: i8) -> Result<(), PmwError> {
+        if steps == 0 {
+            return Ok(());
+        }
+
+        let mut cpi = self.settings.cpi;
+        for _ in 0..steps.unsigned_abs() {
+            cpi = if steps > 0 {
+                self.cpi_presets.next(cpi)
+            } else {
+                self.cpi_presets.previous(cpi)
+            };
+        }
+        self.pmw_driver.set_cpi(cpi)?;
+        self.settings.cpi = cpi;
+        Ok(())
+    }
+
+    /// Reads a single register for diagnostics. Command registers and registers that would
+    /// swallow pending motion are refused.
+    pub fn read_register(&mut self, address: u8) -> Option<Result<u8, PmwError>> {
+        let register = Register::from_address(address)?;
+        if register.is_command() || register.latches_motion() {
+            return None;
+        }
+
+        Some(self.pmw_driver.read_register(register))
+    }
+
+    /// Captures a raw image, then restarts navigation with the current settings.
+    pub fn capture_frame(&mut self) -> Result<SensorImage, PmwError> {
+        let image = self.pmw_driver.capture_frame();
+        self.sensor_info = self.pmw_driver.init()?;
+        self.configure_sensor()?;
+        image
+    }
+}
@@ -0,0 +1,150 @@
+use crate::button_data::{ButtonData, MouseButton};
+use crate::motion_data::MotionData;
+
+/// Largest X/Y magnitude a `MouseReport` can carry. -32768 is left out so the logical range is
+/// symmetric.
+pub const MAX_REPORT_DELTA: i16 = 32767;
+/// Largest X/Y magnitude a `BootMouseReport` can carry.
+pub const MAX_BOOT_REPORT_DELTA: i16 = 127;
+/// Largest wheel magnitude a `MouseReport` can carry.
+pub const MAX_REPORT_WHEEL: i8 = 127;
+
+pub struct MouseReport {
+    // Bytes usage:
+    // bytes 0..1: buttons 1..16, little endian
+    // bytes 2..3: x, little endian
+    // bytes 4..5: y, little endian
+    // byte 6: wheel
+    bytes: [u8; 7],
+}
+
+impl MouseReport {
+    pub const DESCRIPTOR: &'static [u8] = &[
+        0x05, 0x01, // USAGE_PAGE Generic Desktop
+        0x09, 0x02, // USAGE Mouse
+        0xa1, 0x01, // COLLECTION Application
+        0x09, 0x01, // USAGE Pointer
+        0xa1, 0x00, // COLLECTION Physical
+        0x05, 0x09, // USAGE_PAGE Button
+        0x19, 0x01, // USAGE_MINIMUM Button 1
+        0x29, 0x10, // USAGE_MAXIMUM Button 16
+        0x15, 0x00, // LOGICAL_MINIMUM 0
+        0x25, 0x01, // LOGICAL_MAXIMUM 1
+        0x95, 0x10, // REPORT_COUNT 16
+        0x75, 0x01, // REPORT_SIZE 1
+        0x81, 0x02, // INPUT Data,Var,Abs
+        0x05, 0x01, // USAGE_PAGE Generic Desktop
+        0x09, 0x30, // USAGE X
+        0x09, 0x31, // USAGE Y
+        0x16, 0x01, 0x80, // LOGICAL_MINIMUM -32767
+        0x26, 0xff, 0x7f, // LOGICAL_MAXIMUM 32767
+        0x75, 0x10, // REPORT_SIZE 16
+        0x95, 0x02, // REPORT_COUNT 2
+        0x81, 0x06, // INPUT Data,Var,Rel
+        0x09, 0x38, // USAGE Wheel
+        0x15, 0x81, // LOGICAL_MINIMUM -127
+        0x25, 0x7f, // LOGICAL_MAXIMUM 127
+        0x75, 0x08, // REPORT_SIZE 8
+        0x95, 0x01, // REPORT_COUNT 1
+        0x81, 0x06, // INPUT Data,Var,Rel
+        0xc0, // END COLLECTION
+        0xc0, // END COLLECTION
+    ];
+
+    pub fn new(motion_data: MotionData, button_data: ButtonData, wheel: i8) -> Self {
+        let [x_low, x_high] = clamp_delta(motion_data.delta_x, MAX_REPORT_DELTA).to_le_bytes();
+        let [y_low, y_high] = clamp_delta(motion_data.delta_y, MAX_REPORT_DELTA).to_le_bytes();
+        let [buttons_low, buttons_high] = u16::from(button_data).to_le_bytes();
+
+        Self {
+            bytes: [
+                buttons_low,
+                buttons_high,
+                x_low,
+                x_high,
+                y_low,
+                y_high,
+                clamp_wheel(wheel) as u8,
+            ],
+        }
+    }
+}
+
+impl AsRef<[u8]> for MouseReport {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Boot protocol mouse report, with the 8-bit X/Y fields laid out in the HID specification. Only
+/// the left, right and middle buttons fit, and there's no wheel.
+pub struct BootMouseReport {
+    // Bytes usage:
+    // byte 0: bits 0..2 = buttons
+    // byte 1: x
+    // byte 2: y
+    bytes: [u8; 3],
+}
+
+impl BootMouseReport {
+    pub const DESCRIPTOR: &'static [u8] = &[
+        0x05, 0x01, // USAGE_PAGE Generic Desktop
+        0x09, 0x02, // USAGE Mouse
+        0xa1, 0x01, // COLLECTION Application
+        0x09, 0x01, // USAGE Pointer
+        0xa1, 0x00, // COLLECTION Physical
+        0x05, 0x09, // USAGE_PAGE Button
+        0x19, 0x01, // USAGE_MINIMUM Button 1
+        0x29, 0x03, // USAGE_MAXIMUM Button 3
+        0x15, 0x00, // LOGICAL_MINIMUM 0
+        0x25, 0x01, // LOGICAL_MAXIMUM 1
+        0x95, 0x03, // REPORT_COUNT 3
+        0x75, 0x01, // REPORT_SIZE 1
+        0x81, 0x02, // INPUT Data,Var,Abs
+        0x95, 0x01, // REPORT_COUNT 1
+        0x75, 0x05, // REPORT_SIZE 5
+        0x81, 0x01, // INPUT Cnst,Ary,Abs
+        0x05, 0x01, // USAGE_PAGE Generic Desktop
+        0x09, 0x30, // USAGE X
+        0x09, 0x31, // USAGE Y
+        0x15, 0x81, // LOGICAL_MINIMUM -127
+        0x25, 0x7f, // LOGICAL_MAXIMUM 127
+        0x75, 0x08, // REPORT_SIZE 8
+        0x95, 0x02, // REPORT_COUNT 2
+        0x81, 0x06, // INPUT Data,Var,Rel
+        0xc0, // END COLLECTION
+        0xc0, // END COLLECTION
+    ];
+
+    /// Deltas beyond the 8-bit range saturate instead of wrapping around.
+    pub fn new(motion_data: MotionData, button_data: ButtonData) -> Self {
+        Self {
+            bytes: [
+                boot_buttons(button_data),
+                clamp_delta(motion_data.delta_x, MAX_BOOT_REPORT_DELTA) as i8 as u8,
+                clamp_delta(motion_data.delta_y, MAX_BOOT_REPORT_DELTA) as i8 as u8,
+            ],
+        }
+    }
+}
+
+impl AsRef<[u8]> for BootMouseReport {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+fn clamp_delta(delta: i16, max: i16) -> i16 {
+    delta.clamp(-max, max)
+}
+
+fn boot_buttons(button_data: ButtonData) -> u8 {
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+        .into_iter()
+        .filter(|&button| button_data.is_button_pressed(button))
+        .fold(0, |byte, button| byte | (1 << button as u8))
+}
+
+fn clamp_wheel(wheel: i8) -> i8 {
+    wheel.clamp(-MAX_REPORT_WHEEL, MAX_REPORT_WHEEL)
+}
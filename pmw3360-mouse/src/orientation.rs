@@ -0,0 +1,85 @@
+use crate::motion_data::MotionData;
+
+/// Rotation of the motion vector, counter-clockwise in sensor coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// How the sensor is mounted relative to the mouse body.
+///
+/// Applied in the order rotation, X/Y swap, inversion: rotation corrects the mounting angle and
+/// the remaining flags handle mirrored layouts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    pub swap_xy: bool,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl Orientation {
+    pub fn apply(&self, motion_data: MotionData) -> MotionData {
+        let (delta_x, delta_y) = (motion_data.delta_x, motion_data.delta_y);
+
+        let (mut delta_x, mut delta_y) = match self.rotation {
+            Rotation::Deg0 => (delta_x, delta_y),
+            Rotation::Deg90 => (delta_y.saturating_neg(), delta_x),
+            Rotation::Deg180 => (delta_x.saturating_neg(), delta_y.saturating_neg()),
+            Rotation::Deg270 => (delta_y, delta_x.saturating_neg()),
+        };
+        if self.swap_xy {
+            (delta_x, delta_y) = (delta_y, delta_x);
+        }
+        if self.invert_x {
+            delta_x = delta_x.saturating_neg();
+        }
+        if self.invert_y {
+            delta_y = delta_y.saturating_neg();
+        }
+
+        MotionData {
+            delta_x,
+            delta_y,
+            ..motion_data
+        }
+    }
+}
+
+/// One-byte encoding used by settings records: bits 0..1 rotation, bit 2 swap X/Y, bit 3 invert
+/// X, bit 4 invert Y.
+impl From<Orientation> for u8 {
+    fn from(orientation: Orientation) -> Self {
+        let rotation = match orientation.rotation {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 1,
+            Rotation::Deg180 => 2,
+            Rotation::Deg270 => 3,
+        };
+        rotation
+            | (orientation.swap_xy as u8) << 2
+            | (orientation.invert_x as u8) << 3
+            | (orientation.invert_y as u8) << 4
+    }
+}
+
+impl From<u8> for Orientation {
+    fn from(byte: u8) -> Self {
+        let rotation = match byte & 0b11 {
+            0 => Rotation::Deg0,
+            1 => Rotation::Deg90,
+            2 => Rotation::Deg180,
+            _ => Rotation::Deg270,
+        };
+        Self {
+            rotation,
+            swap_xy: byte & (1 << 2) != 0,
+            invert_x: byte & (1 << 3) != 0,
+            invert_y: byte & (1 << 4) != 0,
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use crate::constants::{
+    CPI_STEP, FRAME_CAPTURE_DELAY, INIT_DELAY, MAX_CPI, MIN_CPI, PMW_3360_FIRMWARE,
+    PMW_3360_FIRMWARE_ID, READ_ADDRESS_DATA_DELAY, READ_COMMAND_DELAY, SROM_BYTE_DELAY,
+    SROM_CRC_DELAY, SROM_CRC_PASS, SROM_DOWNLOAD_DELAY, SROM_ENABLE_DELAY, SROM_UPLOAD_ATTEMPTS,
+    WRITE_COMMAND_DELAY,
+};
+use crate::fine_rotation::MAX_ANGLE_TUNE;
+use crate::motion_data::MotionData;
+use crate::power_mode::PowerMode;
+use crate::registers::{
+    AngleSnap, AngleTune, Config1, Config2, FrameCapture, InverseProductId, LiftConfig, LiftHeight,
+    PowerUpReset, ProductId, Register, RegisterValue, Rest1Downshift, Rest2Downshift, RevisionId,
+    RunDownshift, SromEnable, SromId, INVERSE_PRODUCT_ID, PRODUCT_ID,
+};
+use crate::sensor_image::{SensorImage, IMAGE_PIXELS};
+use alloc::vec;
+use alloc::vec::Vec;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, Error as _, OutputPin};
+use embedded_hal::spi::{self, Mode, SpiDevice, MODE_3};
+
+/// SPI mode expected by the PMW3360 (clock idles high, data captured on the rising edge).
+pub const SPI_MODE: Mode = MODE_3;
+
+/// How often init reads the sensor's identity before giving up on an unresponsive bus.
+const IDENTIFY_ATTEMPTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PmwError {
+    /// An SPI transfer failed.
+    Bus(spi::ErrorKind),
+    /// The chip enable pin couldn't be driven.
+    ChipEnable(digital::ErrorKind),
+    /// Something answered on the bus, but it isn't a PMW3360.
+    WrongSensor {
+        product_id: u8,
+        inverse_product_id: u8,
+    },
+    /// The downloaded SROM didn't verify, even after retrying the upload.
+    SromFailed(SromError),
+    /// The sensor didn't respond in time.
+    Timeout,
+    /// The requested CPI is outside 100..=12000 or not a multiple of 100.
+    UnsupportedCpi(u16),
+    /// The requested Angle_Tune correction is outside ±30 degrees.
+    UnsupportedAngle(i8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SromError {
+    /// SROM_ID doesn't match the version of `PMW_3360_FIRMWARE`; 0 means no SROM is running.
+    WrongId(u8),
+    /// The SROM CRC self-test returned something other than 0xBEEF.
+    BadCrc(u16),
+}
+
+/// Identity registers read during init.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SensorInfo {
+    pub product_id: u8,
+    pub revision_id: u8,
+    pub srom_id: u8,
+}
+
+/// Driver for the PMW3360 optical sensor.
+///
+/// `SPI` must not drive the sensor's NCS line itself (for example an `ExclusiveDevice` with
+/// `NoCs`): the sensor needs NCS held low across the tSRAD delay between the address and data
+/// bytes, so the driver toggles `CS` on its own.
+pub struct PmwDriver<SPI, CS, D> {
+    spi: SPI,
+    chip_enable_pin: CS,
+    delay: D,
+    motion_burst_active: bool,
+}
+
+impl<SPI, CS, D> PmwDriver<SPI, CS, D>
+where
+    SPI: SpiDevice,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    pub fn new(spi: SPI, chip_enable_pin: CS, delay: D) -> Self {
+        Self {
+            spi,
+            chip_enable_pin,
+            delay,
+            motion_burst_active: false,
+        }
+    }
+
+    pub fn release(self) -> (SPI, CS, D) {
+        (self.spi, self.chip_enable_pin, self.delay)
+    }
+
+    pub fn init(&mut self) -> Result<SensorInfo, PmwError> {
+        self.set_chip_enable(false)?;
+        self.delay.delay_us(INIT_DELAY.to_micros());
+        self.set_chip_enable(true)?;
+
+        self.write(PowerUpReset::RESET)?;
+
+        self.delay.delay_us(INIT_DELAY.to_micros());
+        let sensor_info = self.identify()?;
+
+        self.read_register(Register::Motion)?;
+        self.read_register(Register::DeltaXL)?;
+        self.read_register(Register::DeltaXH)?;
+        self.read_register(Register::DeltaYL)?;
+        self.read_register(Register::DeltaYH)?;
+
+        // The CRC self-test isn't possible in rest mode, so it stays off until the SROM verified.
+        self.set_rest_mode(false)?;
+
+        let mut attempt = 1;
+        loop {
+            self.download_srom()?;
+            match self.verify_srom() {
+                Ok(()) => break,
+                Err(PmwError::SromFailed(_)) if attempt < SROM_UPLOAD_ATTEMPTS => attempt += 1,
+                Err(error) => return Err(error),
+            }
+        }
+        self.write(Config2(0x00))?;
+
+        Ok(SensorInfo {
+            srom_id: PMW_3360_FIRMWARE_ID,
+            ..sensor_info
+        })
+    }
+
+    /// Checks that the expected SROM version is running and passes its CRC self-test.
+    pub fn verify_srom(&mut self) -> Result<(), PmwError> {
+        let srom_id = self.read::<SromId>()?.0;
+        if srom_id != PMW_3360_FIRMWARE_ID {
+            return Err(PmwError::SromFailed(SromError::WrongId(srom_id)));
+        }
+
+        self.write(SromEnable::CRC_TEST)?;
+        self.delay.delay_us(SROM_CRC_DELAY.to_micros());
+        let crc_upper = self.read_register(Register::DataOutUpper)?;
+        let crc_lower = self.read_register(Register::DataOutLower)?;
+        let crc = u16::from_be_bytes([crc_upper, crc_lower]);
+        if crc != SROM_CRC_PASS {
+            return Err(PmwError::SromFailed(SromError::BadCrc(crc)));
+        }
+
+        Ok(())
+    }
+
+    /// Checks Product_ID and Inverse_Product_ID. A bus where the two don't complement each
+    /// other is treated as a sensor that isn't ready yet and retried; a consistent pair with
+    /// the wrong ID means a different chip is connected.
+    pub fn identify(&mut self) -> Result<SensorInfo, PmwError> {
+        for _ in 0..IDENTIFY_ATTEMPTS {
+            let product_id = self.read::<ProductId>()?.0;
+            let inverse_product_id = self.read::<InverseProductId>()?.0;
+
+            if product_id == PRODUCT_ID && inverse_product_id == INVERSE_PRODUCT_ID {
+                let revision_id = self.read::<RevisionId>()?.0;
+                let srom_id = self.read::<SromId>()?.0;
+                return Ok(SensorInfo {
+                    product_id,
+                    revision_id,
+                    srom_id,
+                });
+            }
+
+            if product_id == !inverse_product_id {
+                return Err(PmwError::WrongSensor {
+                    product_id,
+                    inverse_product_id,
+                });
+            }
+
+            self.delay.delay_us(INIT_DELAY.to_micros());
+        }
+
+        Err(PmwError::Timeout)
+    }
+
+    /// Reads one Motion_Burst frame, which also releases the MOTION pin until new motion is
+    /// pending.
+    pub fn read_motion(&mut self) -> Result<MotionData, PmwError> {
+        // Burst mode is armed by writing Motion_Burst and stays active until any other register
+        // is read or written.
+        if !self.motion_burst_active {
+            self.pmw_write(Register::MotionBurst, vec![0xff])?;
+            self.motion_burst_active = true;
+        }
+
+        Ok(self.pmw_read(Register::MotionBurst, 12)?.into())
+    }
+
+    /// Captures one raw 36x36 image of the surface under the sensor, for diagnosing surface and
+    /// lens problems.
+    ///
+    /// Frame capture stops navigation: `init` has to run again (reset and SROM download) before
+    /// motion can be read.
+    pub fn capture_frame(&mut self) -> Result<SensorImage, PmwError> {
+        self.set_rest_mode(false)?;
+        for value in FrameCapture::START {
+            self.write(value)?;
+        }
+        self.delay.delay_us(FRAME_CAPTURE_DELAY.to_micros());
+
+        Ok(self.pmw_read(Register::RawDataBurst, IMAGE_PIXELS)?.into())
+    }
+
+    /// Sets the resolution through Config1. The sensor supports 100 to 12000 CPI in steps of 100.
+    pub fn set_cpi(&mut self, cpi: u16) -> Result<(), PmwError> {
+        if !(MIN_CPI..=MAX_CPI).contains(&cpi) || !cpi.is_multiple_of(CPI_STEP) {
+            return Err(PmwError::UnsupportedCpi(cpi));
+        }
+
+        self.write(Config1::from_cpi(cpi))
+    }
+
+    pub fn get_cpi(&mut self) -> Result<u16, PmwError> {
+        Ok(self.read::<Config1>()?.cpi())
+    }
+
+    /// Rotates the sensor's X/Y output by up to ±30 degrees through Angle_Tune.
+    pub fn set_angle_tune(&mut self, degrees: i8) -> Result<(), PmwError> {
+        if !(-MAX_ANGLE_TUNE..=MAX_ANGLE_TUNE).contains(&degrees) {
+            return Err(PmwError::UnsupportedAngle(degrees));
+        }
+
+        self.write(AngleTune::from_angle(degrees))
+    }
+
+    pub fn get_angle_tune(&mut self) -> Result<i8, PmwError> {
+        Ok(self.read::<AngleTune>()?.angle())
+    }
+
+    /// Sets the lift-off distance, above which the sensor reports Lift_Stat and stops tracking.
+    pub fn set_lift_height(&mut self, lift_height: LiftHeight) -> Result<(), PmwError> {
+        self.modify(|lift_config: LiftConfig| lift_config.with_lift_height(lift_height))
+    }
+
+    pub fn get_lift_height(&mut self) -> Result<LiftHeight, PmwError> {
+        Ok(self.read::<LiftConfig>()?.lift_height())
+    }
+
+    /// Turns the sensor's built-in angle snapping on or off. It's off after reset.
+    pub fn set_angle_snap(&mut self, enable: bool) -> Result<(), PmwError> {
+        self.modify(|angle_snap: AngleSnap| angle_snap.with_enable(enable))
+    }
+
+    pub fn get_angle_snap(&mut self) -> Result<bool, PmwError> {
+        Ok(self.read::<AngleSnap>()?.enable())
+    }
+
+    /// Lets the sensor drop into its rest modes after a period without motion.
+    pub fn set_rest_mode(&mut self, enable: bool) -> Result<(), PmwError> {
+        self.modify(|config2: Config2| config2.with_rest_en(enable))
+    }
+
+    pub fn get_rest_mode(&mut self) -> Result<bool, PmwError> {
+        Ok(self.read::<Config2>()?.rest_en())
+    }
+
+    /// Programs the downshift timings and rest frame rates, then enables or disables rest mode.
+    /// Safe to call at any time to switch between presets.
+    pub fn set_power_mode(&mut self, power_mode: &PowerMode) -> Result<(), PmwError> {
+        self.write(RunDownshift(power_mode.run_downshift))?;
+        self.write_register_pair(Register::Rest1RateLower, power_mode.rest1_rate)?;
+        self.write(Rest1Downshift(power_mode.rest1_downshift))?;
+        self.write_register_pair(Register::Rest2RateLower, power_mode.rest2_rate)?;
+        self.write(Rest2Downshift(power_mode.rest2_downshift))?;
+        self.write_register_pair(Register::Rest3RateLower, power_mode.rest3_rate)?;
+        self.set_rest_mode(power_mode.rest_enabled)
+    }
+
+    pub fn get_power_mode(&mut self) -> Result<PowerMode, PmwError> {
+        Ok(PowerMode {
+            rest_enabled: self.get_rest_mode()?,
+            run_downshift: self.read::<RunDownshift>()?.0,
+            rest1_rate: self.read_register_pair(Register::Rest1RateLower)?,
+            rest1_downshift: self.read::<Rest1Downshift>()?.0,
+            rest2_rate: self.read_register_pair(Register::Rest2RateLower)?,
+            rest2_downshift: self.read::<Rest2Downshift>()?.0,
+            rest3_rate: self.read_register_pair(Register::Rest3RateLower)?,
+        })
+    }
+
+    pub fn read<R: RegisterValue>(&mut self) -> Result<R, PmwError> {
+        Ok(self.read_register(R::REGISTER)?.into())
+    }
+
+    pub fn write<R: RegisterValue>(&mut self, value: R) -> Result<(), PmwError> {
+        self.write_register(R::REGISTER, value.into())
+    }
+
+    /// Read-modify-write of a single register.
+    pub fn modify<R: RegisterValue>(&mut self, f: impl FnOnce(R) -> R) -> Result<(), PmwError> {
+        let value = self.read::<R>()?;
+        self.write(f(value))
+    }
+
+    pub fn read_register(&mut self, register: Register) -> Result<u8, PmwError> {
+        Ok(self.pmw_read(register, 1)?[0])
+    }
+
+    pub fn write_register(&mut self, register: Register, value: u8) -> Result<(), PmwError> {
+        self.pmw_write(register, vec![value])
+    }
+
+    /// Reads a 16-bit value split across `lower` and the register right after it.
+    fn read_register_pair(&mut self, lower: Register) -> Result<u16, PmwError> {
+        let upper = Register::from_address(lower.address() + 1).unwrap();
+        let lower = self.read_register(lower)?;
+        Ok(u16::from_le_bytes([lower, self.read_register(upper)?]))
+    }
+
+    fn write_register_pair(&mut self, lower: Register, value: u16) -> Result<(), PmwError> {
+        let upper = Register::from_address(lower.address() + 1).unwrap();
+        let [low, high] = value.to_le_bytes();
+        self.write_register(lower, low)?;
+        self.write_register(upper, high)
+    }
+
+    fn download_srom(&mut self) -> Result<(), PmwError> {
+        self.write(SromEnable::INIT)?;
+        self.delay.delay_us(SROM_ENABLE_DELAY.to_micros());
+        self.write(SromEnable::DOWNLOAD)?;
+        self.upload_srom()?;
+        self.delay.delay_us(SROM_DOWNLOAD_DELAY.to_micros());
+
+        Ok(())
+    }
+
+    fn upload_srom(&mut self) -> Result<(), PmwError> {
+        self.motion_burst_active = false;
+        self.set_chip_enable(false)?;
+        let result = self.write_srom_frame();
+        self.set_chip_enable(true)?;
+        result?;
+        self.delay.delay_us(WRITE_COMMAND_DELAY.to_micros());
+
+        Ok(())
+    }
+
+    fn write_srom_frame(&mut self) -> Result<(), PmwError> {
+        self.write_address(true, Register::SromLoadBurst)?;
+
+        for byte in PMW_3360_FIRMWARE {
+            self.delay.delay_us(SROM_BYTE_DELAY.to_micros());
+            self.spi.write(&[byte]).map_err(bus_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn pmw_write(&mut self, register: Register, data: Vec<u8>) -> Result<(), PmwError> {
+        self.motion_burst_active = false;
+        self.pmw_transfer(true, register, data)?;
+        Ok(())
+    }
+
+    fn pmw_read(&mut self, register: Register, count: usize) -> Result<Vec<u8>, PmwError> {
+        // Reading any other register ends burst mode as well.
+        if register != Register::MotionBurst {
+            self.motion_burst_active = false;
+        }
+        self.pmw_transfer(false, register, vec![0xff; count])
+    }
+
+    fn pmw_transfer(
+        &mut self,
+        is_write: bool,
+        register: Register,
+        mut data: Vec<u8>,
+    ) -> Result<Vec<u8>, PmwError> {
+        self.set_chip_enable(false)?;
+        // NCS is raised again even when the transfer fails, so the next command starts cleanly.
+        let result = self.transfer_frame(is_write, register, &mut data);
+        self.set_chip_enable(true)?;
+        result?;
+
+        let command_delay = if is_write {
+            WRITE_COMMAND_DELAY
+        } else {
+            READ_COMMAND_DELAY
+        };
+        self.delay.delay_us(command_delay.to_micros());
+
+        Ok(data)
+    }
+
+    fn transfer_frame(
+        &mut self,
+        is_write: bool,
+        register: Register,
+        data: &mut [u8],
+    ) -> Result<(), PmwError> {
+        self.write_address(is_write, register)?;
+        self.delay.delay_us(READ_ADDRESS_DATA_DELAY.to_micros());
+        self.spi.transfer_in_place(data).map_err(bus_error)
+    }
+
+    fn write_address(&mut self, is_write: bool, register: Register) -> Result<(), PmwError> {
+        let address = register.address();
+        let first_byte = if is_write {
+            (1 << 7) | address
+        } else {
+            !(1 << 7) & address
+        };
+
+        self.spi.write(&[first_byte]).map_err(bus_error)
+    }
+
+    fn set_chip_enable(&mut self, high: bool) -> Result<(), PmwError> {
+        let result = if high {
+            self.chip_enable_pin.set_high()
+        } else {
+            self.chip_enable_pin.set_low()
+        };
+        result.map_err(|error| PmwError::ChipEnable(error.kind()))
+    }
+}
+
+fn bus_error(error: impl spi::Error) -> PmwError {
+    PmwError::Bus(error.kind())
+}
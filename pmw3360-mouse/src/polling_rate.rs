@@ -0,0 +1,79 @@
+/// USB polling rate of the mouse interface, applied as the interrupt endpoint's interval.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PollingRate {
+    Hz125,
+    Hz250,
+    Hz500,
+    #[default]
+    Hz1000,
+}
+
+impl PollingRate {
+    pub fn interval_ms(self) -> u8 {
+        match self {
+            PollingRate::Hz125 => 8,
+            PollingRate::Hz250 => 4,
+            PollingRate::Hz500 => 2,
+            PollingRate::Hz1000 => 1,
+        }
+    }
+
+    pub fn hz(self) -> u16 {
+        1000 / self.interval_ms() as u16
+    }
+}
+
+/// Encodes the interval in milliseconds.
+impl From<PollingRate> for u8 {
+    fn from(polling_rate: PollingRate) -> Self {
+        polling_rate.interval_ms()
+    }
+}
+
+impl TryFrom<u8> for PollingRate {
+    type Error = u8;
+
+    fn try_from(interval_ms: u8) -> Result<Self, Self::Error> {
+        match interval_ms {
+            8 => Ok(PollingRate::Hz125),
+            4 => Ok(PollingRate::Hz250),
+            2 => Ok(PollingRate::Hz500),
+            1 => Ok(PollingRate::Hz1000),
+            _ => Err(interval_ms),
+        }
+    }
+}
+
+/// Paces USB reports to one per polling interval on a millisecond clock. It only decides when a
+/// report goes out: motion is read whenever the sensor asserts MOTION and buttons are sampled on
+/// every tick, whatever the polling rate.
+#[derive(Debug)]
+pub struct ReportTimer {
+    interval_ms: u32,
+    next_ms: u32,
+}
+
+impl ReportTimer {
+    pub fn new(polling_rate: PollingRate, now_ms: u32) -> Self {
+        Self {
+            interval_ms: polling_rate.interval_ms() as u32,
+            next_ms: now_ms,
+        }
+    }
+
+    /// Whether the next report is due, scheduling the one after it. Intervals missed while the
+    /// loop was busy are skipped rather than caught up with back to back reports.
+    pub fn is_due(&mut self, now_ms: u32) -> bool {
+        let late_ms = now_ms.wrapping_sub(self.next_ms) as i32;
+        if late_ms < 0 {
+            return false;
+        }
+
+        self.next_ms = if late_ms as u32 >= self.interval_ms {
+            now_ms.wrapping_add(self.interval_ms)
+        } else {
+            self.next_ms.wrapping_add(self.interval_ms)
+        };
+        true
+    }
+}
@@ -0,0 +1,98 @@
+//! Rest mode and downshift timing.
+//!
+//! With rest mode enabled the sensor drops from Run into progressively slower Rest1, Rest2 and
+//! Rest3 frame rates when no motion is seen, trading wake-up latency for current draw.
+
+/// Rest mode settings, in the units of the datasheet registers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PowerMode {
+    /// Config2 REST_EN. When off, the sensor stays in Run mode and the other fields have no
+    /// effect.
+    pub rest_enabled: bool,
+    /// Time without motion before Run drops to Rest1: `run_downshift * 10 ms`.
+    pub run_downshift: u8,
+    /// Rest1 frame period: `(rest1_rate + 1) * 1 ms`.
+    pub rest1_rate: u16,
+    /// Time in Rest1 before dropping to Rest2: `rest1_downshift * 320 * Rest1 period`.
+    pub rest1_downshift: u8,
+    /// Rest2 frame period: `(rest2_rate + 1) * 1 ms`.
+    pub rest2_rate: u16,
+    /// Time in Rest2 before dropping to Rest3: `rest2_downshift * 32 * Rest2 period`.
+    pub rest2_downshift: u8,
+    /// Rest3 frame period: `(rest3_rate + 1) * 1 ms`.
+    pub rest3_rate: u16,
+}
+
+impl PowerMode {
+    /// Always in Run mode, for the lowest latency. This is how `PmwDriver::init` leaves the
+    /// sensor.
+    pub const PERFORMANCE: Self = Self {
+        rest_enabled: false,
+        ..Self::BALANCED
+    };
+
+    /// Rest mode with the datasheet's default timings: Rest1 after 500 ms, Rest2 after about 10 s
+    /// and Rest3 after about 10 minutes without motion.
+    pub const BALANCED: Self = Self {
+        rest_enabled: true,
+        run_downshift: 0x32,
+        rest1_rate: 0x0000,
+        rest1_downshift: 0x1f,
+        rest2_rate: 0x0063,
+        rest2_downshift: 0xbc,
+        rest3_rate: 0x01f3,
+    };
+
+    /// Rest mode with short downshift times, for battery powered builds: Rest1 after 100 ms,
+    /// Rest2 after about 1 s and Rest3 after about 30 s.
+    pub const POWER_SAVER: Self = Self {
+        rest_enabled: true,
+        run_downshift: 0x0a,
+        rest1_rate: 0x0000,
+        rest1_downshift: 0x03,
+        rest2_rate: 0x0063,
+        rest2_downshift: 0x09,
+        rest3_rate: 0x01f3,
+    };
+}
+
+impl PowerMode {
+    pub const ENCODED_SIZE: usize = 10;
+
+    /// Encoding used by settings records, multi-byte fields little endian.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_SIZE] {
+        let [rest1_rate_l, rest1_rate_h] = self.rest1_rate.to_le_bytes();
+        let [rest2_rate_l, rest2_rate_h] = self.rest2_rate.to_le_bytes();
+        let [rest3_rate_l, rest3_rate_h] = self.rest3_rate.to_le_bytes();
+        [
+            self.rest_enabled as u8,
+            self.run_downshift,
+            rest1_rate_l,
+            rest1_rate_h,
+            self.rest1_downshift,
+            rest2_rate_l,
+            rest2_rate_h,
+            self.rest2_downshift,
+            rest3_rate_l,
+            rest3_rate_h,
+        ]
+    }
+
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_SIZE]) -> Self {
+        Self {
+            rest_enabled: bytes[0] != 0,
+            run_downshift: bytes[1],
+            rest1_rate: u16::from_le_bytes([bytes[2], bytes[3]]),
+            rest1_downshift: bytes[4],
+            rest2_rate: u16::from_le_bytes([bytes[5], bytes[6]]),
+            rest2_downshift: bytes[7],
+            rest3_rate: u16::from_le_bytes([bytes[8], bytes[9]]),
+        }
+    }
+}
+
+impl Default for PowerMode {
+    fn default() -> Self {
+        Self::PERFORMANCE
+    }
+}
@@ -0,0 +1,79 @@
+/// How many quadrature transitions one detent of the encoder spans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepsPerDetent {
+    One = 1,
+    Two = 2,
+    Four = 4,
+}
+
+/// Decodes a two-channel quadrature encoder, such as a scroll wheel, into detents.
+///
+/// Bounce on one contact shows up as a transition and its reverse, which cancel. Transitions that
+/// change both channels at once can't be told apart from bounce and are ignored. Detents are only
+/// counted when the encoder arrives in a rest state, and a detent that lost transitions is still
+/// counted once more than half of it was seen, so the count stays in step with the detents.
+#[derive(Debug)]
+pub struct QuadratureDecoder {
+    steps_per_detent: StepsPerDetent,
+    rest_state: u8,
+    state: u8,
+    steps: i8,
+    detents: i32,
+}
+
+impl QuadratureDecoder {
+    /// `a` and `b` are the channel levels at power-up, taken as a rest position.
+    pub fn new(steps_per_detent: StepsPerDetent, a: bool, b: bool) -> Self {
+        let state = encode(a, b);
+        Self {
+            steps_per_detent,
+            rest_state: state,
+            state,
+            steps: 0,
+            detents: 0,
+        }
+    }
+
+    /// Feeds the current channel levels. Call it on every edge of either channel, or poll faster
+    /// than the encoder can make two transitions.
+    pub fn update(&mut self, a: bool, b: bool) {
+        let state = encode(a, b);
+        let direction = match (self.state, state) {
+            (0b00, 0b10) | (0b10, 0b11) | (0b11, 0b01) | (0b01, 0b00) => 1,
+            (0b00, 0b01) | (0b01, 0b11) | (0b11, 0b10) | (0b10, 0b00) => -1,
+            _ => 0,
+        };
+        self.state = state;
+        if direction == 0 {
+            return;
+        }
+
+        self.steps += direction;
+        if self.is_rest_state(state) {
+            let steps_per_detent = self.steps_per_detent as i8;
+            if self.steps * 2 > steps_per_detent {
+                self.detents += 1;
+            } else if self.steps * 2 < -steps_per_detent {
+                self.detents -= 1;
+            }
+            self.steps = 0;
+        }
+    }
+
+    /// Returns the detents decoded since the last call, positive for A leading B.
+    pub fn take_detents(&mut self) -> i32 {
+        core::mem::take(&mut self.detents)
+    }
+
+    fn is_rest_state(&self, state: u8) -> bool {
+        match self.steps_per_detent {
+            StepsPerDetent::One => true,
+            StepsPerDetent::Two => state == self.rest_state || state == self.rest_state ^ 0b11,
+            StepsPerDetent::Four => state == self.rest_state,
+        }
+    }
+}
+
+fn encode(a: bool, b: bool) -> u8 {
+    ((a as u8) << 1) | b as u8
+}
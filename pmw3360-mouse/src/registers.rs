@@ -0,0 +1,478 @@
+//! The PMW3360 register map, with typed values for registers that hold more than a plain number.
+//!
+//! Value types follow the datasheet's field names: getters read a field, `with_*` methods return
+//! a copy with the field changed, so a read-modify-write looks like
+//! `driver.modify(|config2: Config2| config2.with_rest_en(false))`.
+
+/// Register addresses, as listed in the datasheet's register table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Register {
+    ProductId = 0x00,
+    RevisionId = 0x01,
+    Motion = 0x02,
+    DeltaXL = 0x03,
+    DeltaXH = 0x04,
+    DeltaYL = 0x05,
+    DeltaYH = 0x06,
+    Squal = 0x07,
+    RawDataSum = 0x08,
+    MaximumRawData = 0x09,
+    MinimumRawData = 0x0a,
+    ShutterLower = 0x0b,
+    ShutterUpper = 0x0c,
+    Control = 0x0d,
+    Config1 = 0x0f,
+    Config2 = 0x10,
+    AngleTune = 0x11,
+    FrameCapture = 0x12,
+    SromEnable = 0x13,
+    RunDownshift = 0x14,
+    Rest1RateLower = 0x15,
+    Rest1RateUpper = 0x16,
+    Rest1Downshift = 0x17,
+    Rest2RateLower = 0x18,
+    Rest2RateUpper = 0x19,
+    Rest2Downshift = 0x1a,
+    Rest3RateLower = 0x1b,
+    Rest3RateUpper = 0x1c,
+    Observation = 0x24,
+    DataOutLower = 0x25,
+    DataOutUpper = 0x26,
+    RawDataDump = 0x29,
+    SromId = 0x2a,
+    MinSqRun = 0x2b,
+    RawDataThreshold = 0x2c,
+    Config5 = 0x2f,
+    PowerUpReset = 0x3a,
+    Shutdown = 0x3b,
+    InverseProductId = 0x3f,
+    LiftCutoffTune3 = 0x41,
+    AngleSnap = 0x42,
+    LiftCutoffTune1 = 0x4a,
+    MotionBurst = 0x50,
+    LiftCutoffTuneTimeout = 0x58,
+    LiftCutoffTuneMinLength = 0x5a,
+    SromLoadBurst = 0x62,
+    LiftConfig = 0x63,
+    RawDataBurst = 0x64,
+    LiftCutoffTune2 = 0x65,
+    LiftCutoffTune2Timeout = 0x71,
+    LiftCutoffTune2MinLength = 0x72,
+    PwmPeriodCnt = 0x73,
+    PwmWidthCnt = 0x74,
+}
+
+impl Register {
+    pub const ALL: [Register; 53] = [
+        Register::ProductId,
+        Register::RevisionId,
+        Register::Motion,
+        Register::DeltaXL,
+        Register::DeltaXH,
+        Register::DeltaYL,
+        Register::DeltaYH,
+        Register::Squal,
+        Register::RawDataSum,
+        Register::MaximumRawData,
+        Register::MinimumRawData,
+        Register::ShutterLower,
+        Register::ShutterUpper,
+        Register::Control,
+        Register::Config1,
+        Register::Config2,
+        Register::AngleTune,
+        Register::FrameCapture,
+        Register::SromEnable,
+        Register::RunDownshift,
+        Register::Rest1RateLower,
+        Register::Rest1RateUpper,
+        Register::Rest1Downshift,
+        Register::Rest2RateLower,
+        Register::Rest2RateUpper,
+        Register::Rest2Downshift,
+        Register::Rest3RateLower,
+        Register::Rest3RateUpper,
+        Register::Observation,
+        Register::DataOutLower,
+        Register::DataOutUpper,
+        Register::RawDataDump,
+        Register::SromId,
+        Register::MinSqRun,
+        Register::RawDataThreshold,
+        Register::Config5,
+        Register::PowerUpReset,
+        Register::Shutdown,
+        Register::InverseProductId,
+        Register::LiftCutoffTune3,
+        Register::AngleSnap,
+        Register::LiftCutoffTune1,
+        Register::MotionBurst,
+        Register::LiftCutoffTuneTimeout,
+        Register::LiftCutoffTuneMinLength,
+        Register::SromLoadBurst,
+        Register::LiftConfig,
+        Register::RawDataBurst,
+        Register::LiftCutoffTune2,
+        Register::LiftCutoffTune2Timeout,
+        Register::LiftCutoffTune2MinLength,
+        Register::PwmPeriodCnt,
+        Register::PwmWidthCnt,
+    ];
+
+    pub const fn address(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_address(address: u8) -> Option<Register> {
+        Self::ALL
+            .into_iter()
+            .find(|register| register.address() == address)
+    }
+
+    /// Registers the sensor ignores writes to.
+    pub fn is_read_only(self) -> bool {
+        matches!(
+            self,
+            Register::ProductId
+                | Register::RevisionId
+                | Register::DeltaXL
+                | Register::DeltaXH
+                | Register::DeltaYL
+                | Register::DeltaYH
+                | Register::Squal
+                | Register::RawDataSum
+                | Register::MaximumRawData
+                | Register::MinimumRawData
+                | Register::ShutterLower
+                | Register::ShutterUpper
+                | Register::Observation
+                | Register::DataOutLower
+                | Register::DataOutUpper
+                | Register::SromId
+                | Register::InverseProductId
+        )
+    }
+
+    /// Registers with side effects on write, or that are only meaningful as part of a burst,
+    /// and so must not be read back or restored blindly.
+    pub fn is_command(self) -> bool {
+        matches!(
+            self,
+            Register::FrameCapture
+                | Register::SromEnable
+                | Register::RawDataDump
+                | Register::PowerUpReset
+                | Register::Shutdown
+                | Register::MotionBurst
+                | Register::SromLoadBurst
+                | Register::RawDataBurst
+        )
+    }
+
+    /// Registers whose reads latch or clear the pending motion, so reading them outside the
+    /// motion path loses counts.
+    pub fn latches_motion(self) -> bool {
+        matches!(
+            self,
+            Register::Motion
+                | Register::DeltaXL
+                | Register::DeltaXH
+                | Register::DeltaYL
+                | Register::DeltaYH
+        )
+    }
+}
+
+/// A typed value of a single register.
+pub trait RegisterValue: Copy + From<u8> + Into<u8> {
+    const REGISTER: Register;
+}
+
+macro_rules! register_value {
+    ($name:ident, $register:ident) => {
+        impl From<u8> for $name {
+            fn from(bits: u8) -> Self {
+                Self(bits)
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl RegisterValue for $name {
+            const REGISTER: Register = Register::$register;
+        }
+    };
+}
+
+fn bit(bits: u8, index: u8) -> bool {
+    bits & (1 << index) != 0
+}
+
+fn with_bit(bits: u8, index: u8, set: bool) -> u8 {
+    if set {
+        bits | (1 << index)
+    } else {
+        bits & !(1 << index)
+    }
+}
+
+pub const PRODUCT_ID: u8 = 0x42;
+pub const INVERSE_PRODUCT_ID: u8 = !PRODUCT_ID;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProductId(pub u8);
+register_value!(ProductId, ProductId);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevisionId(pub u8);
+register_value!(RevisionId, RevisionId);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InverseProductId(pub u8);
+register_value!(InverseProductId, InverseProductId);
+
+/// Operating mode reported in Motion's OP_Mode field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpMode {
+    Run,
+    Rest1,
+    Rest2,
+    Rest3,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Motion(pub u8);
+register_value!(Motion, Motion);
+
+impl Motion {
+    /// MOT: motion occurred since the last report.
+    pub fn mot(self) -> bool {
+        bit(self.0, 7)
+    }
+
+    /// Lift_Stat: the chip is lifted off the surface.
+    pub fn lift_stat(self) -> bool {
+        bit(self.0, 3)
+    }
+
+    pub fn op_mode(self) -> OpMode {
+        match (self.0 >> 1) & 0b11 {
+            0 => OpMode::Run,
+            1 => OpMode::Rest1,
+            2 => OpMode::Rest2,
+            _ => OpMode::Rest3,
+        }
+    }
+
+    /// Frame_Pix_First: the current Raw_Data_Burst byte is the first pixel of a frame.
+    pub fn frame_pix_first(self) -> bool {
+        bit(self.0, 0)
+    }
+}
+
+/// Resolution for X (and Y unless Config2's RPT_Mod is set), in 100 CPI steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config1(pub u8);
+register_value!(Config1, Config1);
+
+impl Config1 {
+    pub fn cpi(self) -> u16 {
+        (self.0 as u16 + 1) * 100
+    }
+
+    /// `cpi` must already be validated to 100..=12000 in steps of 100.
+    pub fn from_cpi(cpi: u16) -> Self {
+        Self((cpi / 100 - 1) as u8)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config2(pub u8);
+register_value!(Config2, Config2);
+
+impl Config2 {
+    /// Rest_En: automatic downshift to the rest modes.
+    pub fn rest_en(self) -> bool {
+        bit(self.0, 5)
+    }
+
+    pub fn with_rest_en(self, rest_en: bool) -> Self {
+        Self(with_bit(self.0, 5, rest_en))
+    }
+
+    /// RPT_Mod: Config1 sets X resolution only and Config5 sets Y.
+    pub fn rpt_mod(self) -> bool {
+        bit(self.0, 2)
+    }
+
+    pub fn with_rpt_mod(self, rpt_mod: bool) -> Self {
+        Self(with_bit(self.0, 2, rpt_mod))
+    }
+}
+
+/// Y resolution when Config2's RPT_Mod is set, encoded like Config1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config5(pub u8);
+register_value!(Config5, Config5);
+
+impl Config5 {
+    pub fn cpi(self) -> u16 {
+        Config1(self.0).cpi()
+    }
+
+    pub fn from_cpi(cpi: u16) -> Self {
+        Self(Config1::from_cpi(cpi).0)
+    }
+}
+
+/// Rotation applied by the sensor to its X/Y output, in degrees (two's complement, ±30).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AngleTune(pub u8);
+register_value!(AngleTune, AngleTune);
+
+impl AngleTune {
+    pub fn angle(self) -> i8 {
+        self.0 as i8
+    }
+
+    pub fn from_angle(angle: i8) -> Self {
+        Self(angle as u8)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameCapture(pub u8);
+register_value!(FrameCapture, FrameCapture);
+
+impl FrameCapture {
+    /// Written in this order to start a frame capture.
+    pub const START: [Self; 2] = [Self(0x83), Self(0xc5)];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SromEnable(pub u8);
+register_value!(SromEnable, SromEnable);
+
+impl SromEnable {
+    /// Prepares the sensor for an SROM download.
+    pub const INIT: Self = Self(0x1d);
+    /// Starts the SROM download through SROM_Load_Burst.
+    pub const DOWNLOAD: Self = Self(0x18);
+    /// Starts the SROM CRC self-test; the result appears in Data_Out after 10 ms.
+    pub const CRC_TEST: Self = Self(0x15);
+}
+
+/// Time in run mode without motion before downshifting to rest 1, in 10 ms units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunDownshift(pub u8);
+register_value!(RunDownshift, RunDownshift);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rest1Downshift(pub u8);
+register_value!(Rest1Downshift, Rest1Downshift);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rest2Downshift(pub u8);
+register_value!(Rest2Downshift, Rest2Downshift);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Observation(pub u8);
+register_value!(Observation, Observation);
+
+impl Observation {
+    /// Bit 6 is set while the downloaded SROM code is running.
+    pub fn srom_run(self) -> bool {
+        bit(self.0, 6)
+    }
+}
+
+/// Version of the SROM image currently running, 0 when none was downloaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SromId(pub u8);
+register_value!(SromId, SromId);
+
+/// Lift-off distance selected by Lift_Config's Lift_Height field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiftHeight {
+    Mm2,
+    Mm3,
+}
+
+/// Encodes the distance in millimetres.
+impl From<LiftHeight> for u8 {
+    fn from(lift_height: LiftHeight) -> Self {
+        match lift_height {
+            LiftHeight::Mm2 => 2,
+            LiftHeight::Mm3 => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for LiftHeight {
+    type Error = u8;
+
+    fn try_from(millimetres: u8) -> Result<Self, Self::Error> {
+        match millimetres {
+            2 => Ok(LiftHeight::Mm2),
+            3 => Ok(LiftHeight::Mm3),
+            _ => Err(millimetres),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiftConfig(pub u8);
+register_value!(LiftConfig, LiftConfig);
+
+impl LiftConfig {
+    pub fn lift_height(self) -> LiftHeight {
+        match self.0 & 0b11 {
+            0b11 => LiftHeight::Mm3,
+            _ => LiftHeight::Mm2,
+        }
+    }
+
+    pub fn with_lift_height(self, lift_height: LiftHeight) -> Self {
+        let field = match lift_height {
+            LiftHeight::Mm2 => 0b10,
+            LiftHeight::Mm3 => 0b11,
+        };
+        Self((self.0 & !0b11) | field)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AngleSnap(pub u8);
+register_value!(AngleSnap, AngleSnap);
+
+impl AngleSnap {
+    /// Enable: snap near-horizontal and near-vertical motion to the axes.
+    pub fn enable(self) -> bool {
+        bit(self.0, 7)
+    }
+
+    pub fn with_enable(self, enable: bool) -> Self {
+        Self(with_bit(self.0, 7, enable))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PowerUpReset(pub u8);
+register_value!(PowerUpReset, PowerUpReset);
+
+impl PowerUpReset {
+    pub const RESET: Self = Self(0x5a);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shutdown(pub u8);
+register_value!(Shutdown, Shutdown);
+
+impl Shutdown {
+    pub const SHUTDOWN: Self = Self(0xb6);
+}
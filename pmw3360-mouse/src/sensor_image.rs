@@ -0,0 +1,41 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+pub const IMAGE_WIDTH: usize = 36;
+pub const IMAGE_HEIGHT: usize = 36;
+pub const IMAGE_PIXELS: usize = IMAGE_WIDTH * IMAGE_HEIGHT;
+
+/// One raw frame from the sensor's pixel array, as returned by `PmwDriver::capture_frame`.
+///
+/// Pixels are kept in the order the sensor sends them over Raw_Data_Burst, row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SensorImage {
+    pixels: Vec<u8>,
+}
+
+impl SensorImage {
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * IMAGE_WIDTH + x]
+    }
+
+    /// Encodes the image as a binary PGM (P5) file.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut pgm = format!("P5\n{} {}\n255\n", IMAGE_WIDTH, IMAGE_HEIGHT).into_bytes();
+        pgm.extend_from_slice(&self.pixels);
+        pgm
+    }
+}
+
+impl From<Vec<u8>> for SensorImage {
+    fn from(pixels: Vec<u8>) -> Self {
+        if pixels.len() != IMAGE_PIXELS {
+            panic!("Tried to decode an invalid sensor image.");
+        }
+
+        Self { pixels }
+    }
+}
@@ -0,0 +1,162 @@
+//! User-adjustable configuration, kept in flash by [`crate::settings_store`] so changes survive a
+//! power cycle without reflashing.
+
+use crate::button_data::MAX_BUTTONS;
+use crate::button_map::{ButtonAction, ButtonMap};
+use crate::fine_rotation::split_angle_correction;
+use crate::motion_pipeline::MotionPipeline;
+use crate::orientation::{Orientation, Rotation};
+use crate::pmw_driver::{PmwDriver, PmwError};
+use crate::polling_rate::PollingRate;
+use crate::power_mode::PowerMode;
+use crate::registers::LiftHeight;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+
+/// Layout version of `Settings::to_bytes`, stored with every record.
+pub const SETTINGS_VERSION: u8 = 2;
+/// Size of the encoded settings.
+pub const SETTINGS_SIZE: usize = BUTTON_ACTIONS_END + 1;
+
+// Version 1 ended after the button actions; version 2 appended the polling rate.
+const BUTTON_ACTIONS_END: usize = 19 + MAX_BUTTONS * ButtonAction::ENCODED_SIZE;
+const SETTINGS_V1_SIZE: usize = BUTTON_ACTIONS_END;
+
+// Encodes `None` for optional thresholds.
+const NONE: u8 = 0xff;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub cpi: u16,
+    pub orientation: Orientation,
+    /// Mounting angle correction in tenths of a degree, see `split_angle_correction`.
+    pub angle_correction: i16,
+    pub sensor_angle_snap: bool,
+    pub axis_snap_threshold: Option<u8>,
+    pub lift_height: LiftHeight,
+    pub lift_squal_threshold: Option<u8>,
+    pub power_mode: PowerMode,
+    /// Action of each physical button; buttons the board doesn't have are ignored.
+    pub button_actions: [ButtonAction; MAX_BUTTONS],
+    pub polling_rate: PollingRate,
+}
+
+impl Settings {
+    pub const DEFAULT: Self = Self {
+        cpi: 800,
+        orientation: Orientation {
+            rotation: Rotation::Deg0,
+            swap_xy: false,
+            invert_x: false,
+            invert_y: false,
+        },
+        angle_correction: 0,
+        sensor_angle_snap: false,
+        axis_snap_threshold: None,
+        lift_height: LiftHeight::Mm2,
+        lift_squal_threshold: None,
+        power_mode: PowerMode::PERFORMANCE,
+        button_actions: ButtonMap::<MAX_BUTTONS>::standard().into_actions(),
+        polling_rate: PollingRate::Hz1000,
+    };
+
+    /// Encodes the settings, multi-byte fields little endian.
+    pub fn to_bytes(&self) -> [u8; SETTINGS_SIZE] {
+        let mut bytes = [0; SETTINGS_SIZE];
+        bytes[0..2].copy_from_slice(&self.cpi.to_le_bytes());
+        bytes[2] = self.orientation.into();
+        bytes[3..5].copy_from_slice(&self.angle_correction.to_le_bytes());
+        bytes[5] = self.sensor_angle_snap as u8;
+        bytes[6] = self.axis_snap_threshold.unwrap_or(NONE);
+        bytes[7] = self.lift_height.into();
+        bytes[8] = self.lift_squal_threshold.unwrap_or(NONE);
+        bytes[9..19].copy_from_slice(&self.power_mode.to_bytes());
+        for (chunk, action) in bytes[19..BUTTON_ACTIONS_END]
+            .chunks_exact_mut(ButtonAction::ENCODED_SIZE)
+            .zip(self.button_actions)
+        {
+            chunk.copy_from_slice(&action.to_bytes());
+        }
+        bytes[BUTTON_ACTIONS_END] = self.polling_rate.into();
+        bytes
+    }
+
+    /// Decodes settings written by `to_bytes` of layout `version`, filling in defaults for
+    /// fields older layouts didn't have. `None` if the version is unknown, the length doesn't
+    /// match it or a field holds an invalid value.
+    pub fn from_bytes(version: u8, bytes: &[u8]) -> Option<Self> {
+        let polling_rate = match (version, bytes.len()) {
+            (1, SETTINGS_V1_SIZE) => Self::DEFAULT.polling_rate,
+            (2, SETTINGS_SIZE) => PollingRate::try_from(bytes[BUTTON_ACTIONS_END]).ok()?,
+            _ => return None,
+        };
+
+        let mut button_actions = [ButtonAction::Disabled; MAX_BUTTONS];
+        for (action, chunk) in button_actions
+            .iter_mut()
+            .zip(bytes[19..BUTTON_ACTIONS_END].chunks_exact(ButtonAction::ENCODED_SIZE))
+        {
+            *action = ButtonAction::from_bytes(chunk.try_into().unwrap())?;
+        }
+
+        Some(Self {
+            cpi: u16::from_le_bytes([bytes[0], bytes[1]]),
+            orientation: bytes[2].into(),
+            angle_correction: i16::from_le_bytes([bytes[3], bytes[4]]),
+            sensor_angle_snap: bytes[5] != 0,
+            axis_snap_threshold: optional(bytes[6]),
+            lift_height: LiftHeight::try_from(bytes[7]).ok()?,
+            lift_squal_threshold: optional(bytes[8]),
+            power_mode: PowerMode::from_bytes(bytes[9..19].try_into().unwrap()),
+            button_actions,
+            polling_rate,
+        })
+    }
+
+    /// Writes the sensor side of the settings. Runs after `PmwDriver::init`, which resets the
+    /// sensor.
+    pub fn configure_sensor<SPI, CS, D>(
+        &self,
+        pmw_driver: &mut PmwDriver<SPI, CS, D>,
+    ) -> Result<(), PmwError>
+    where
+        SPI: SpiDevice,
+        CS: OutputPin,
+        D: DelayNs,
+    {
+        let (angle_tune, _) = split_angle_correction(self.angle_correction);
+        pmw_driver.set_cpi(self.cpi)?;
+        pmw_driver.set_lift_height(self.lift_height)?;
+        pmw_driver.set_power_mode(&self.power_mode)?;
+        pmw_driver.set_angle_tune(angle_tune)?;
+        pmw_driver.set_angle_snap(self.sensor_angle_snap)
+    }
+
+    pub fn configure_pipeline(&self, motion_pipeline: &mut MotionPipeline) {
+        let (_, fine_rotation) = split_angle_correction(self.angle_correction);
+        motion_pipeline.set_orientation(self.orientation);
+        motion_pipeline.set_fine_rotation(fine_rotation);
+        motion_pipeline.set_axis_snap(self.axis_snap_threshold);
+        motion_pipeline
+            .lift_filter_mut()
+            .set_squal_threshold(self.lift_squal_threshold);
+    }
+
+    /// Button map for a board with `N` buttons, taking the first `N` actions.
+    pub fn button_map<const N: usize>(&self) -> ButtonMap<N> {
+        ButtonMap::new(core::array::from_fn(|button| {
+            self.button_actions.get(button).copied().unwrap_or_default()
+        }))
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn optional(byte: u8) -> Option<u8> {
+    (byte != NONE).then_some(byte)
+}
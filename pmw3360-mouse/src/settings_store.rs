@@ -0,0 +1,199 @@
+//! Emulated EEPROM for `Settings` on a few pages of internal flash.
+//!
+//! Saves are appended as fixed-size records, filling one page after the other, so every page is
+//! erased only once per lap around the region. A record is:
+//!
+//! | offset | size | field                                              |
+//! |--------|------|----------------------------------------------------|
+//! | 0      | 2    | magic `RECORD_MAGIC`                               |
+//! | 2      | 1    | layout version of the payload                      |
+//! | 3      | 1    | payload length                                     |
+//! | 4      | 4    | sequence number, incremented on every save         |
+//! | 8      | 116  | payload, `Settings::to_bytes`                      |
+//! | 124    | 4    | CRC-32 of bytes 0..124                             |
+//! | 128    | 2    | commit marker, programmed to 0 once the rest is in |
+//!
+//! Multi-byte fields are little endian and every slot is `RECORD_SIZE` bytes. A save interrupted
+//! by a power loss leaves a record without commit marker, which is skipped on load, so the
+//! previous record stays in effect. A page is only erased right before the first record goes into
+//! it, and it never holds the newest record at that point.
+
+use crate::settings::{Settings, SETTINGS_SIZE, SETTINGS_VERSION};
+
+pub const RECORD_SIZE: usize = 132;
+const RECORD_MAGIC: u16 = 0x5350;
+const HEADER_SIZE: usize = 8;
+const MAX_PAYLOAD_SIZE: usize = 116;
+const CRC_OFFSET: usize = HEADER_SIZE + MAX_PAYLOAD_SIZE;
+const COMMIT_OFFSET: usize = CRC_OFFSET + 4;
+const COMMITTED: u16 = 0x0000;
+const ERASED: u8 = 0xff;
+
+const _: () = assert!(SETTINGS_SIZE <= MAX_PAYLOAD_SIZE);
+
+/// A region of flash pages set aside for settings, addressed from the start of the region.
+///
+/// Writes are at even offsets with an even length and only ever go to erased bytes, which is what
+/// the STM32F1 flash controller (16-bit programming) and most NOR flash can do.
+pub trait SettingsFlash {
+    type Error;
+
+    const PAGE_SIZE: usize;
+    const PAGE_COUNT: usize;
+
+    fn read(&mut self, offset: usize, bytes: &mut [u8]) -> Result<(), Self::Error>;
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<(), Self::Error>;
+    fn erase_page(&mut self, page: usize) -> Result<(), Self::Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    page: usize,
+    index: usize,
+}
+
+pub struct SettingsStore<F> {
+    flash: F,
+    // Newest committed record and its sequence number, `None` before the first save.
+    newest: Option<(Slot, u32)>,
+}
+
+impl<F: SettingsFlash> SettingsStore<F> {
+    const SLOTS_PER_PAGE: usize = F::PAGE_SIZE / RECORD_SIZE;
+
+    pub fn new(flash: F) -> Self {
+        assert!(
+            F::PAGE_COUNT >= 2,
+            "Wear leveling needs at least two pages."
+        );
+        Self {
+            flash,
+            newest: None,
+        }
+    }
+
+    pub fn release(self) -> F {
+        self.flash
+    }
+
+    /// Scans the region for the newest valid record. Records of older layout versions are
+    /// upgraded. Returns `None` when nothing was saved yet, or only with a layout version this
+    /// firmware doesn't understand.
+    pub fn load(&mut self) -> Result<Option<Settings>, F::Error> {
+        self.newest = None;
+        let mut settings = None;
+
+        for page in 0..F::PAGE_COUNT {
+            for index in 0..Self::SLOTS_PER_PAGE {
+                let slot = Slot { page, index };
+                let mut record = [0; RECORD_SIZE];
+                self.flash.read(Self::offset(slot, 0), &mut record)?;
+
+                let Some(sequence) = committed_sequence(&record) else {
+                    continue;
+                };
+                let is_newer = self
+                    .newest
+                    .is_none_or(|(_, newest)| sequence.wrapping_sub(newest) as i32 > 0);
+                if !is_newer {
+                    continue;
+                }
+
+                self.newest = Some((slot, sequence));
+                settings = decode_payload(&record);
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Appends a record with `settings`. Call `load` first so it continues after the newest
+    /// record instead of starting over.
+    pub fn save(&mut self, settings: &Settings) -> Result<(), F::Error> {
+        let (slot, sequence) = match self.newest {
+            Some((newest, sequence)) => (self.free_slot_after(newest)?, sequence.wrapping_add(1)),
+            None => {
+                let slot = Slot { page: 0, index: 0 };
+                self.flash.erase_page(slot.page)?;
+                (slot, 0)
+            }
+        };
+
+        let mut record = [ERASED; RECORD_SIZE];
+        record[0..2].copy_from_slice(&RECORD_MAGIC.to_le_bytes());
+        record[2] = SETTINGS_VERSION;
+        record[3] = SETTINGS_SIZE as u8;
+        record[4..8].copy_from_slice(&sequence.to_le_bytes());
+        record[HEADER_SIZE..HEADER_SIZE + SETTINGS_SIZE].copy_from_slice(&settings.to_bytes());
+        let crc = crc32(&record[..CRC_OFFSET]);
+        record[CRC_OFFSET..COMMIT_OFFSET].copy_from_slice(&crc.to_le_bytes());
+
+        self.flash
+            .write(Self::offset(slot, 0), &record[..COMMIT_OFFSET])?;
+        self.flash
+            .write(Self::offset(slot, COMMIT_OFFSET), &COMMITTED.to_le_bytes())?;
+
+        self.newest = Some((slot, sequence));
+        Ok(())
+    }
+
+    fn offset(slot: Slot, field: usize) -> usize {
+        slot.page * F::PAGE_SIZE + slot.index * RECORD_SIZE + field
+    }
+
+    /// First fully erased slot after `newest`, moving on to (and erasing) the next page when the
+    /// current one is used up.
+    fn free_slot_after(&mut self, newest: Slot) -> Result<Slot, F::Error> {
+        for index in newest.index + 1..Self::SLOTS_PER_PAGE {
+            let slot = Slot {
+                page: newest.page,
+                index,
+            };
+            let mut record = [0; RECORD_SIZE];
+            self.flash.read(Self::offset(slot, 0), &mut record)?;
+            if record.iter().all(|&byte| byte == ERASED) {
+                return Ok(slot);
+            }
+        }
+
+        let slot = Slot {
+            page: (newest.page + 1) % F::PAGE_COUNT,
+            index: 0,
+        };
+        self.flash.erase_page(slot.page)?;
+        Ok(slot)
+    }
+}
+
+/// Sequence number of a complete record, whatever its layout version.
+fn committed_sequence(record: &[u8; RECORD_SIZE]) -> Option<u32> {
+    let magic = u16::from_le_bytes([record[0], record[1]]);
+    let commit = u16::from_le_bytes([record[COMMIT_OFFSET], record[COMMIT_OFFSET + 1]]);
+    let crc = u32::from_le_bytes(record[CRC_OFFSET..COMMIT_OFFSET].try_into().unwrap());
+    if magic != RECORD_MAGIC || commit != COMMITTED || crc != crc32(&record[..CRC_OFFSET]) {
+        return None;
+    }
+
+    Some(u32::from_le_bytes(record[4..8].try_into().unwrap()))
+}
+
+fn decode_payload(record: &[u8; RECORD_SIZE]) -> Option<Settings> {
+    let length = (record[3] as usize).min(MAX_PAYLOAD_SIZE);
+    Settings::from_bytes(record[2], &record[HEADER_SIZE..HEADER_SIZE + length])
+}
+
+/// CRC-32 (IEEE 802.3), bitwise to stay small; records are only checked at boot and on save.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,677 @@
+//! Software model of the PMW3360 for exercising `PmwDriver` on a host.
+//!
+//! `Pmw3360Sim` hands out an SPI device, a chip enable pin, a MOTION pin and a delay that all
+//! share one model. The model decodes the framing produced by the driver (address byte with the
+//! write bit in bit 7, followed by data), keeps a register file, accepts the SROM download
+//! sequence and serves Motion_Burst frames from a script and Raw_Data_Burst images after a frame
+//! capture. Time only advances through the delay and through clocking bytes at the configured SCK
+//! frequency, and every datasheet timing rule that is broken is recorded as a [`Violation`].
+
+use crate::constants::PMW_3360_FIRMWARE;
+use crate::registers::{FrameCapture, PowerUpReset, Register, SromEnable};
+use crate::sensor_image::IMAGE_PIXELS;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::spi::{self, ErrorKind, Operation, SpiDevice};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::rc::Rc;
+use std::vec::Vec;
+
+// Datasheet values, kept separate from the driver's constants so the model checks them
+// independently.
+const T_SRAD_NS: u64 = 160_000;
+const T_SRAD_MOTBR_NS: u64 = 35_000;
+const T_SWW_NS: u64 = 180_000;
+const T_SRW_NS: u64 = 20_000;
+const T_LOAD_NS: u64 = 15_000;
+const T_POWER_UP_NS: u64 = 50_000_000;
+const T_SROM_ENABLE_NS: u64 = 10_000_000;
+const T_SROM_CRC_NS: u64 = 10_000_000;
+const T_FRAME_CAPTURE_NS: u64 = 20_000_000;
+
+const DEFAULT_REGISTERS: [(Register, u8); 18] = [
+    (Register::ProductId, 0x42),
+    (Register::RevisionId, 0x01),
+    (Register::ShutterLower, 0x12),
+    (Register::Control, 0x02),
+    (Register::Config1, 0x31),
+    (Register::Config2, 0x20),
+    (Register::RunDownshift, 0x32),
+    (Register::Rest1RateLower, 0x00),
+    (Register::Rest1Downshift, 0x1f),
+    (Register::Rest2RateLower, 0x63),
+    (Register::Rest2Downshift, 0xbc),
+    (Register::Rest3RateLower, 0xf3),
+    (Register::Rest3RateUpper, 0x01),
+    (Register::MinSqRun, 0x10),
+    (Register::RawDataThreshold, 0x0a),
+    (Register::Config5, 0x31),
+    (Register::InverseProductId, 0xbd),
+    (Register::LiftConfig, 0x02),
+];
+
+/// A datasheet rule broken by the SPI master.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// Data was clocked before tSRAD (tSRAD_MOTBR for Motion_Burst) passed after the address.
+    ReadAddressDataDelay { address: u8, elapsed_ns: u64 },
+    /// A command started before tSWW/tSWR (after a write) or tSRW/tSRR (after a read) passed.
+    CommandSpacing { address: u8, elapsed_ns: u64 },
+    /// Two SROM bytes were sent less than 15 us apart.
+    SromByteSpacing { index: usize, elapsed_ns: u64 },
+    /// SROM_Enable was set to 0x18 less than 10 ms after 0x1d, or without 0x1d at all.
+    SromEnableDelay { elapsed_ns: Option<u64> },
+    /// Data_Out was read less than 10 ms after starting the SROM CRC self-test.
+    SromCrcDelay { elapsed_ns: u64 },
+    /// The sensor was accessed less than 50 ms after Power_Up_Reset.
+    AccessDuringPowerUp { address: u8 },
+    /// Motion_Burst was read without writing it first.
+    MotionBurstNotArmed,
+    /// Raw_Data_Burst read without a frame capture, or sooner than 20 ms after starting one.
+    FrameCaptureDelay { elapsed_ns: Option<u64> },
+    /// SCK was clocked while NCS was high.
+    ChipNotEnabled,
+}
+
+/// One scripted sensor frame, served by the next Motion or Motion_Burst read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimMotion {
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub lifted: bool,
+    pub squal: u8,
+    pub shutter: u16,
+}
+
+impl SimMotion {
+    pub fn new(delta_x: i16, delta_y: i16) -> Self {
+        Self {
+            delta_x,
+            delta_y,
+            lifted: false,
+            squal: 0x40,
+            shutter: 0x0012,
+        }
+    }
+
+    fn burst(&self) -> [u8; 12] {
+        let mut motion = 0u8;
+        if self.delta_x != 0 || self.delta_y != 0 {
+            motion |= 1 << 7;
+        }
+        if self.lifted {
+            motion |= 1 << 3;
+        }
+
+        let [delta_x_l, delta_x_h] = self.delta_x.to_le_bytes();
+        let [delta_y_l, delta_y_h] = self.delta_y.to_le_bytes();
+        let [shutter_l, shutter_h] = self.shutter.to_le_bytes();
+
+        [
+            motion, 0x00, delta_x_l, delta_x_h, delta_y_l, delta_y_h, self.squal, 0x80, 0xa0, 0x60,
+            shutter_h, shutter_l,
+        ]
+    }
+}
+
+impl Default for SimMotion {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+#[derive(Debug)]
+pub struct SimSpiError;
+
+impl spi::Error for SimSpiError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+struct Frame {
+    address: Option<u8>,
+    is_write: bool,
+    address_end_ns: u64,
+    last_byte_start_ns: u64,
+    last_byte_end_ns: u64,
+    data_index: usize,
+    burst: Option<Vec<u8>>,
+    srom: Vec<u8>,
+}
+
+struct FinishedFrame {
+    is_write: bool,
+    end_ns: u64,
+}
+
+struct State {
+    registers: [u8; 0x80],
+    now_ns: u64,
+    byte_ns: u64,
+    chip_enabled: bool,
+    frame: Option<Frame>,
+    last_frame: Option<FinishedFrame>,
+    reset_at_ns: Option<u64>,
+    srom_enable_at_ns: Option<u64>,
+    srom_download_armed: bool,
+    srom_crc_test_at_ns: Option<u64>,
+    srom_intact: bool,
+    corrupt_downloads: usize,
+    motion_burst_armed: bool,
+    motion_script: VecDeque<SimMotion>,
+    frame_capture_armed: bool,
+    frame_capture_at_ns: Option<u64>,
+    image: Vec<u8>,
+    failing_transactions: usize,
+    violations: Vec<Violation>,
+}
+
+impl State {
+    fn new(sck_hz: u32) -> Self {
+        let mut state = Self {
+            registers: [0; 0x80],
+            now_ns: 0,
+            byte_ns: 8_000_000_000 / sck_hz as u64,
+            chip_enabled: false,
+            frame: None,
+            last_frame: None,
+            reset_at_ns: None,
+            srom_enable_at_ns: None,
+            srom_download_armed: false,
+            srom_crc_test_at_ns: None,
+            srom_intact: false,
+            corrupt_downloads: 0,
+            motion_burst_armed: false,
+            motion_script: VecDeque::new(),
+            frame_capture_armed: false,
+            frame_capture_at_ns: None,
+            image: (0..IMAGE_PIXELS)
+                .map(|index| (index % 0x80) as u8)
+                .collect(),
+            failing_transactions: 0,
+            violations: Vec::new(),
+        };
+        state.reset_registers();
+        state
+    }
+
+    fn reset_registers(&mut self) {
+        self.registers = [0; 0x80];
+        for (register, value) in DEFAULT_REGISTERS {
+            self.registers[register.address() as usize] = value;
+        }
+        self.srom_enable_at_ns = None;
+        self.srom_download_armed = false;
+        self.srom_crc_test_at_ns = None;
+        self.srom_intact = false;
+        self.motion_burst_armed = false;
+        self.frame_capture_armed = false;
+        self.frame_capture_at_ns = None;
+    }
+
+    fn violation(&mut self, violation: Violation) {
+        self.violations.push(violation);
+    }
+
+    fn select(&mut self) {
+        if !self.chip_enabled {
+            self.chip_enabled = true;
+            self.frame = Some(Frame {
+                address: None,
+                is_write: false,
+                address_end_ns: 0,
+                last_byte_start_ns: 0,
+                last_byte_end_ns: 0,
+                data_index: 0,
+                burst: None,
+                srom: Vec::new(),
+            });
+        }
+    }
+
+    fn deselect(&mut self) {
+        if !self.chip_enabled {
+            return;
+        }
+        self.chip_enabled = false;
+
+        let Some(frame) = self.frame.take() else {
+            return;
+        };
+        if frame.address.is_none() {
+            return;
+        }
+        if frame.address == Some(Register::SromLoadBurst.address()) && frame.is_write {
+            self.finish_srom_download(&frame.srom);
+        }
+        self.last_frame = Some(FinishedFrame {
+            is_write: frame.is_write,
+            end_ns: frame.last_byte_end_ns,
+        });
+    }
+
+    fn exchange(&mut self, mosi: u8) -> u8 {
+        let start_ns = self.now_ns;
+        self.now_ns += self.byte_ns;
+        let end_ns = self.now_ns;
+
+        if !self.chip_enabled {
+            self.violation(Violation::ChipNotEnabled);
+            return 0xff;
+        }
+
+        let mut frame = self.frame.take().expect("selected without a frame");
+        let miso = match frame.address {
+            None => {
+                self.begin_command(&mut frame, mosi, start_ns);
+                frame.address_end_ns = end_ns;
+                0x00
+            }
+            Some(address) => {
+                let miso = if frame.is_write {
+                    self.write_data(&mut frame, address, mosi, start_ns);
+                    0x00
+                } else {
+                    self.read_data(&mut frame, address, start_ns)
+                };
+                frame.data_index += 1;
+                miso
+            }
+        };
+        frame.last_byte_start_ns = start_ns;
+        frame.last_byte_end_ns = end_ns;
+        self.frame = Some(frame);
+
+        miso
+    }
+
+    fn begin_command(&mut self, frame: &mut Frame, first_byte: u8, start_ns: u64) {
+        let address = first_byte & 0x7f;
+        frame.address = Some(address);
+        frame.is_write = first_byte & (1 << 7) != 0;
+
+        if let Some(reset_at_ns) = self.reset_at_ns {
+            if start_ns < reset_at_ns + T_POWER_UP_NS {
+                self.violation(Violation::AccessDuringPowerUp { address });
+            }
+        }
+
+        if let Some(last_frame) = &self.last_frame {
+            let required_ns = if last_frame.is_write {
+                T_SWW_NS
+            } else {
+                T_SRW_NS
+            };
+            let elapsed_ns = start_ns - last_frame.end_ns;
+            if elapsed_ns < required_ns {
+                self.violation(Violation::CommandSpacing {
+                    address,
+                    elapsed_ns,
+                });
+            }
+        }
+
+        if !frame.is_write && address == Register::MotionBurst.address() {
+            if !self.motion_burst_armed {
+                self.violation(Violation::MotionBurstNotArmed);
+                frame.burst = Some(vec![0; 12]);
+            } else {
+                let motion = self.motion_script.pop_front().unwrap_or_default();
+                frame.burst = Some(motion.burst().to_vec());
+            }
+        }
+
+        if !frame.is_write && address == Register::RawDataBurst.address() {
+            let elapsed_ns = self.frame_capture_at_ns.map(|at_ns| start_ns - at_ns);
+            if elapsed_ns.is_none_or(|elapsed_ns| elapsed_ns < T_FRAME_CAPTURE_NS) {
+                self.violation(Violation::FrameCaptureDelay { elapsed_ns });
+            }
+            frame.burst = Some(self.image.clone());
+        }
+    }
+
+    fn write_data(&mut self, frame: &mut Frame, address: u8, value: u8, start_ns: u64) {
+        if address == Register::SromLoadBurst.address() {
+            let previous_ns = if frame.data_index == 0 {
+                frame.address_end_ns
+            } else {
+                frame.last_byte_start_ns
+            };
+            let elapsed_ns = start_ns - previous_ns;
+            if elapsed_ns < T_LOAD_NS {
+                self.violation(Violation::SromByteSpacing {
+                    index: frame.data_index,
+                    elapsed_ns,
+                });
+            }
+            frame.srom.push(value);
+        } else if frame.data_index == 0 {
+            self.write_register(address, value);
+        }
+    }
+
+    fn read_data(&mut self, frame: &mut Frame, address: u8, start_ns: u64) -> u8 {
+        if frame.data_index == 0 {
+            let required_ns = if address == Register::MotionBurst.address() {
+                T_SRAD_MOTBR_NS
+            } else {
+                T_SRAD_NS
+            };
+            let elapsed_ns = start_ns - frame.address_end_ns;
+            if elapsed_ns < required_ns {
+                self.violation(Violation::ReadAddressDataDelay {
+                    address,
+                    elapsed_ns,
+                });
+            }
+        }
+
+        match &frame.burst {
+            Some(burst) => burst.get(frame.data_index).copied().unwrap_or(0x00),
+            None => self.read_register(address),
+        }
+    }
+
+    fn write_register(&mut self, address: u8, value: u8) {
+        let register = Register::from_address(address);
+        if register != Some(Register::MotionBurst) {
+            self.motion_burst_armed = false;
+        }
+
+        match register {
+            Some(Register::PowerUpReset) if value == PowerUpReset::RESET.0 => {
+                self.reset_registers();
+                self.reset_at_ns = Some(self.now_ns);
+            }
+            Some(Register::MotionBurst) => self.motion_burst_armed = true,
+            Some(Register::SromEnable) => {
+                if value == SromEnable::INIT.0 {
+                    self.srom_enable_at_ns = Some(self.now_ns);
+                } else if value == SromEnable::DOWNLOAD.0 {
+                    let elapsed_ns = self.srom_enable_at_ns.map(|at_ns| self.now_ns - at_ns);
+                    if elapsed_ns.is_none_or(|elapsed_ns| elapsed_ns < T_SROM_ENABLE_NS) {
+                        self.violation(Violation::SromEnableDelay { elapsed_ns });
+                    }
+                    self.srom_download_armed = true;
+                } else if value == SromEnable::CRC_TEST.0 {
+                    self.srom_crc_test_at_ns = Some(self.now_ns);
+                    let crc: u16 = if self.srom_intact { 0xbeef } else { 0x0000 };
+                    let [crc_upper, crc_lower] = crc.to_be_bytes();
+                    self.registers[Register::DataOutUpper.address() as usize] = crc_upper;
+                    self.registers[Register::DataOutLower.address() as usize] = crc_lower;
+                }
+                self.registers[address as usize] = value;
+            }
+            Some(Register::FrameCapture) => {
+                if value == FrameCapture::START[0].0 {
+                    self.frame_capture_armed = true;
+                } else if value == FrameCapture::START[1].0 && self.frame_capture_armed {
+                    // The capture takes over the sensor, the SROM has to be downloaded again.
+                    self.frame_capture_at_ns = Some(self.now_ns);
+                    self.registers[Register::SromId.address() as usize] = 0x00;
+                    self.registers[Register::Observation.address() as usize] &= !(1 << 6);
+                    self.srom_intact = false;
+                }
+                self.registers[address as usize] = value;
+            }
+            Some(register) if register.is_read_only() => {}
+            _ => self.registers[address as usize & 0x7f] = value,
+        }
+    }
+
+    fn read_register(&mut self, address: u8) -> u8 {
+        // Motion_Burst itself is served as a burst and never gets here.
+        self.motion_burst_armed = false;
+
+        if address == Register::Motion.address() {
+            // Reading Motion latches the accumulated deltas into the Delta registers.
+            let motion = self.motion_script.pop_front().unwrap_or_default();
+            let burst = motion.burst();
+            self.registers[Register::DeltaXL.address() as usize] = burst[2];
+            self.registers[Register::DeltaXH.address() as usize] = burst[3];
+            self.registers[Register::DeltaYL.address() as usize] = burst[4];
+            self.registers[Register::DeltaYH.address() as usize] = burst[5];
+            return burst[0];
+        }
+
+        if address == Register::DataOutUpper.address()
+            || address == Register::DataOutLower.address()
+        {
+            if let Some(test_at_ns) = self.srom_crc_test_at_ns {
+                let elapsed_ns = self.now_ns - test_at_ns;
+                if elapsed_ns < T_SROM_CRC_NS {
+                    self.violation(Violation::SromCrcDelay { elapsed_ns });
+                }
+            }
+        }
+
+        self.registers[address as usize & 0x7f]
+    }
+
+    fn finish_srom_download(&mut self, image: &[u8]) {
+        if !self.srom_download_armed {
+            return;
+        }
+        self.srom_download_armed = false;
+
+        if image.len() != PMW_3360_FIRMWARE.len() {
+            return;
+        }
+
+        // A corrupted download still reports the version from the image header, only the CRC
+        // self-test catches it.
+        self.registers[Register::SromId.address() as usize] = image[1];
+        self.registers[Register::Observation.address() as usize] |= 1 << 6;
+        self.srom_intact = image == PMW_3360_FIRMWARE;
+        if self.corrupt_downloads > 0 {
+            self.corrupt_downloads -= 1;
+            self.srom_intact = false;
+        }
+    }
+}
+
+/// Shared handle to the simulated sensor.
+#[derive(Clone)]
+pub struct Pmw3360Sim {
+    state: Rc<RefCell<State>>,
+}
+
+impl Pmw3360Sim {
+    /// Creates a sensor clocked at the datasheet's maximum SCK frequency of 2 MHz.
+    pub fn new() -> Self {
+        Self::with_sck_frequency(2_000_000)
+    }
+
+    pub fn with_sck_frequency(sck_hz: u32) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State::new(sck_hz))),
+        }
+    }
+
+    pub fn spi(&self) -> SimSpi {
+        SimSpi {
+            state: self.state.clone(),
+        }
+    }
+
+    pub fn chip_enable(&self) -> SimChipEnable {
+        SimChipEnable {
+            state: self.state.clone(),
+        }
+    }
+
+    /// The MOTION output, low while a scripted frame with motion is waiting to be read.
+    pub fn motion_pin(&self) -> SimMotionPin {
+        SimMotionPin {
+            state: self.state.clone(),
+        }
+    }
+
+    pub fn delay(&self) -> SimDelay {
+        SimDelay {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Appends a frame to the motion script.
+    pub fn queue_motion(&self, motion: SimMotion) {
+        self.state.borrow_mut().motion_script.push_back(motion);
+    }
+
+    pub fn pending_motion(&self) -> usize {
+        self.state.borrow().motion_script.len()
+    }
+
+    pub fn register(&self, register: Register) -> u8 {
+        self.state.borrow().registers[register.address() as usize]
+    }
+
+    pub fn set_register(&self, register: Register, value: u8) {
+        self.state.borrow_mut().registers[register.address() as usize] = value;
+    }
+
+    /// Whether a valid SROM image was downloaded since the last reset.
+    pub fn srom_loaded(&self) -> bool {
+        self.register(Register::SromId) != 0
+    }
+
+    /// Replaces the image served by Raw_Data_Burst. It must hold 36x36 pixels.
+    pub fn set_image(&self, pixels: &[u8]) {
+        assert_eq!(pixels.len(), IMAGE_PIXELS);
+        self.state.borrow_mut().image = pixels.to_vec();
+    }
+
+    /// Corrupts the next `count` SROM downloads, so they fail the CRC self-test.
+    pub fn corrupt_srom_downloads(&self, count: usize) {
+        self.state.borrow_mut().corrupt_downloads = count;
+    }
+
+    /// Makes the next `count` SPI transactions fail without touching the sensor.
+    pub fn fail_transactions(&self, count: usize) {
+        self.state.borrow_mut().failing_transactions = count;
+    }
+
+    pub fn now_ns(&self) -> u64 {
+        self.state.borrow().now_ns
+    }
+
+    pub fn violations(&self) -> Vec<Violation> {
+        self.state.borrow().violations.clone()
+    }
+
+    pub fn clear_violations(&self) {
+        self.state.borrow_mut().violations.clear();
+    }
+}
+
+impl Default for Pmw3360Sim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SimSpi {
+    state: Rc<RefCell<State>>,
+}
+
+impl spi::ErrorType for SimSpi {
+    type Error = SimSpiError;
+}
+
+impl SpiDevice for SimSpi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let mut state = self.state.borrow_mut();
+        if state.failing_transactions > 0 {
+            state.failing_transactions -= 1;
+            return Err(SimSpiError);
+        }
+
+        for operation in operations {
+            match operation {
+                Operation::Read(words) => {
+                    for word in words.iter_mut() {
+                        *word = state.exchange(0xff);
+                    }
+                }
+                Operation::Write(words) => {
+                    for word in words.iter() {
+                        state.exchange(*word);
+                    }
+                }
+                Operation::Transfer(read, write) => {
+                    for index in 0..read.len().max(write.len()) {
+                        let miso = state.exchange(write.get(index).copied().unwrap_or(0xff));
+                        if let Some(word) = read.get_mut(index) {
+                            *word = miso;
+                        }
+                    }
+                }
+                Operation::TransferInPlace(words) => {
+                    for word in words.iter_mut() {
+                        *word = state.exchange(*word);
+                    }
+                }
+                Operation::DelayNs(ns) => state.now_ns += *ns as u64,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SimChipEnable {
+    state: Rc<RefCell<State>>,
+}
+
+impl digital::ErrorType for SimChipEnable {
+    type Error = Infallible;
+}
+
+impl OutputPin for SimChipEnable {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.state.borrow_mut().select();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.state.borrow_mut().deselect();
+        Ok(())
+    }
+}
+
+pub struct SimMotionPin {
+    state: Rc<RefCell<State>>,
+}
+
+impl digital::ErrorType for SimMotionPin {
+    type Error = Infallible;
+}
+
+impl InputPin for SimMotionPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.is_low().map(|low| !low)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        let state = self.state.borrow();
+        // Navigation only runs with the SROM loaded.
+        Ok(state.registers[Register::SromId.address() as usize] != 0
+            && state
+                .motion_script
+                .iter()
+                .any(|motion| motion.delta_x != 0 || motion.delta_y != 0))
+    }
+}
+
+pub struct SimDelay {
+    state: Rc<RefCell<State>>,
+}
+
+impl DelayNs for SimDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.state.borrow_mut().now_ns += ns as u64;
+    }
+}
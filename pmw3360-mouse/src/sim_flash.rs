@@ -0,0 +1,113 @@
+//! RAM model of the settings flash region, for exercising `SettingsStore` on a host.
+//!
+//! It enforces what real NOR flash allows (writes at even offsets, only into erased bytes) and can
+//! cut the power in the middle of a write.
+
+use crate::settings_store::SettingsFlash;
+use std::vec;
+use std::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimFlashError {
+    Unaligned,
+    NotErased { offset: usize },
+    OutOfRange,
+    PowerLoss,
+}
+
+/// Four 2 KiB pages, like the settings region of the STM32F103 firmware.
+#[derive(Clone, Debug)]
+pub struct SimFlash {
+    bytes: Vec<u8>,
+    erase_counts: Vec<u32>,
+    // Half-words still programmed before the simulated power loss.
+    power_left: Option<usize>,
+}
+
+impl SimFlash {
+    pub fn new() -> Self {
+        Self {
+            bytes: vec![0xff; Self::PAGE_SIZE * Self::PAGE_COUNT],
+            erase_counts: vec![0; Self::PAGE_COUNT],
+            power_left: None,
+        }
+    }
+
+    /// Loses power after `half_words` more half-words were programmed. Until `restore_power`,
+    /// every write and erase fails.
+    pub fn lose_power_after(&mut self, half_words: usize) {
+        self.power_left = Some(half_words);
+    }
+
+    pub fn restore_power(&mut self) {
+        self.power_left = None;
+    }
+
+    pub fn erase_counts(&self) -> &[u32] {
+        &self.erase_counts
+    }
+
+    fn check_range(&self, offset: usize, len: usize) -> Result<(), SimFlashError> {
+        if offset + len > self.bytes.len() {
+            return Err(SimFlashError::OutOfRange);
+        }
+        Ok(())
+    }
+}
+
+impl Default for SimFlash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettingsFlash for SimFlash {
+    type Error = SimFlashError;
+
+    const PAGE_SIZE: usize = 2048;
+    const PAGE_COUNT: usize = 4;
+
+    fn read(&mut self, offset: usize, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.check_range(offset, bytes.len())?;
+        bytes.copy_from_slice(&self.bytes[offset..offset + bytes.len()]);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<(), Self::Error> {
+        if !offset.is_multiple_of(2) || !bytes.len().is_multiple_of(2) {
+            return Err(SimFlashError::Unaligned);
+        }
+        self.check_range(offset, bytes.len())?;
+
+        for (index, half_word) in bytes.chunks_exact(2).enumerate() {
+            let offset = offset + index * 2;
+            if let Some(power_left) = &mut self.power_left {
+                if *power_left == 0 {
+                    return Err(SimFlashError::PowerLoss);
+                }
+                *power_left -= 1;
+            }
+            if half_word == [0xff, 0xff] {
+                continue;
+            }
+            if self.bytes[offset..offset + 2] != [0xff, 0xff] {
+                return Err(SimFlashError::NotErased { offset });
+            }
+            self.bytes[offset..offset + 2].copy_from_slice(half_word);
+        }
+        Ok(())
+    }
+
+    fn erase_page(&mut self, page: usize) -> Result<(), Self::Error> {
+        if page >= Self::PAGE_COUNT {
+            return Err(SimFlashError::OutOfRange);
+        }
+        if self.power_left == Some(0) {
+            return Err(SimFlashError::PowerLoss);
+        }
+
+        self.bytes[page * Self::PAGE_SIZE..(page + 1) * Self::PAGE_SIZE].fill(0xff);
+        self.erase_counts[page] += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use pmw3360_mouse::axis_snap::{AxisSnap, MAX_AXIS_SNAP_THRESHOLD};
+use pmw3360_mouse::motion_data::MotionData;
+
+fn apply(axis_snap: &AxisSnap, delta_x: i16, delta_y: i16) -> (i16, i16) {
+    let motion_data = axis_snap.apply(MotionData {
+        delta_x,
+        delta_y,
+        ..Default::default()
+    });
+    (motion_data.delta_x, motion_data.delta_y)
+}
+
+#[test]
+fn motion_within_the_threshold_snaps_to_the_x_axis() {
+    // tan(10°) is about 0.176.
+    let axis_snap = AxisSnap::new(Some(10));
+
+    assert_eq!(apply(&axis_snap, 100, 17), (100, 0));
+    assert_eq!(apply(&axis_snap, -100, -17), (-100, 0));
+    assert_eq!(apply(&axis_snap, 100, 18), (100, 18));
+}
+
+#[test]
+fn motion_within_the_threshold_snaps_to_the_y_axis() {
+    let axis_snap = AxisSnap::new(Some(10));
+
+    assert_eq!(apply(&axis_snap, 17, 100), (0, 100));
+    assert_eq!(apply(&axis_snap, -17, -100), (0, -100));
+    assert_eq!(apply(&axis_snap, 18, 100), (18, 100));
+}
+
+#[test]
+fn threshold_is_capped_below_45_degrees() {
+    // tan(44°) is about 0.966.
+    let axis_snap = AxisSnap::new(Some(60));
+
+    assert_eq!(axis_snap.threshold(), Some(MAX_AXIS_SNAP_THRESHOLD));
+    assert_eq!(apply(&axis_snap, 100, 96), (100, 0));
+    assert_eq!(apply(&axis_snap, 100, 97), (100, 97));
+}
+
+#[test]
+fn disabled_snap_passes_motion_through() {
+    let axis_snap = AxisSnap::new(None);
+
+    assert_eq!(apply(&axis_snap, 100, 1), (100, 1));
+    assert_eq!(apply(&axis_snap, 0, 5), (0, 5));
+}
@@ -0,0 +1,61 @@
+use pmw3360_mouse::button_data::{ButtonData, MouseButton};
+use pmw3360_mouse::button_map::{ButtonAction, ButtonMap};
+use pmw3360_mouse::consumer_report::VOLUME_UP;
+use pmw3360_mouse::keyboard_report::modifiers::LEFT_CTRL;
+use pmw3360_mouse::motion_data::MotionData;
+use pmw3360_mouse::mouse_report::MouseReport;
+
+#[test]
+fn physical_buttons_report_in_hid_order() {
+    // The firmware wires its pins in `MouseButton` order: left, right, middle, back, forward.
+    // HID numbers them buttons 1 to 5, so bit n of the report is physical button n.
+    let physical = [
+        (MouseButton::Left, 0b00001),
+        (MouseButton::Right, 0b00010),
+        (MouseButton::Middle, 0b00100),
+        (MouseButton::Back, 0b01000),
+        (MouseButton::Forward, 0b10000),
+    ];
+    let mut button_map = ButtonMap::<5>::standard();
+
+    for (button, report_bits) in physical {
+        let mut button_data = ButtonData::default();
+        button_data.set_pressed(button as usize, true);
+
+        let mapped = button_map.apply(button_data);
+        let report = MouseReport::new(MotionData::default(), mapped.mouse, 0);
+        assert_eq!(report.as_ref()[..2], [report_bits, 0], "{:?}", button);
+    }
+}
+
+#[test]
+fn left_handed_swaps_left_and_right() {
+    let mut button_map = ButtonMap::<5>::left_handed();
+
+    let mapped = button_map.apply(ButtonData::from_bits(0b001));
+    assert!(mapped.mouse.is_button_pressed(MouseButton::Right));
+    assert!(!mapped.mouse.is_button_pressed(MouseButton::Left));
+}
+
+#[test]
+fn actions_fill_the_keyboard_consumer_and_cpi_fields() {
+    let mut button_map = ButtonMap::new([
+        ButtonAction::Keyboard {
+            modifiers: LEFT_CTRL,
+            key: 0x06,
+        },
+        ButtonAction::Consumer(VOLUME_UP),
+        ButtonAction::CpiUp,
+        ButtonAction::Disabled,
+    ]);
+
+    let mapped = button_map.apply(ButtonData::from_bits(0b1111));
+    assert_eq!(mapped.mouse, ButtonData::default());
+    assert_eq!(mapped.keyboard.modifiers, LEFT_CTRL);
+    assert_eq!(mapped.keyboard.keys, [0x06, 0, 0, 0, 0, 0]);
+    assert_eq!(mapped.consumer, VOLUME_UP);
+    assert_eq!(mapped.cpi_steps, 1);
+
+    // Holding the CPI button doesn't keep stepping.
+    assert_eq!(button_map.apply(ButtonData::from_bits(0b1111)).cpi_steps, 0);
+}
@@ -0,0 +1,199 @@
+use pmw3360_mouse::button_map::ButtonAction;
+use pmw3360_mouse::config_server::ConfigServer;
+use pmw3360_mouse::consumer_report::MUTE;
+use pmw3360_mouse::mouse::Mouse;
+use pmw3360_mouse::pmw_driver::PmwDriver;
+use pmw3360_mouse::polling_rate::PollingRate;
+use pmw3360_mouse::registers::{Config1, Register};
+use pmw3360_mouse::settings::Settings;
+use pmw3360_mouse::settings_store::SettingsStore;
+use pmw3360_mouse::sim::{Pmw3360Sim, SimChipEnable, SimDelay, SimSpi};
+use pmw3360_mouse::sim_flash::SimFlash;
+use pmw3360_protocol::message::{
+    AngleSnap, Command, LiftConfig, Reply, Request, Response, Status, FRAME_CHUNK_SIZE,
+    PROTOCOL_VERSION, REPORT_SIZE,
+};
+
+type SimMouse = Mouse<SimSpi, SimChipEnable, SimDelay, SimFlash, 5>;
+
+fn boot(sim: &Pmw3360Sim, flash: SimFlash) -> SimMouse {
+    let mut pmw_driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    let sensor_info = pmw_driver.init().unwrap();
+    let mut settings_store = SettingsStore::new(flash);
+    let settings = settings_store.load().unwrap().unwrap_or_default();
+    let mut mouse = Mouse::new(pmw_driver, sensor_info, settings_store, settings);
+    mouse.configure_sensor().unwrap();
+    mouse
+}
+
+/// Sends `request` through its report encoding, like the host would.
+fn send(
+    server: &mut ConfigServer,
+    mouse: &mut SimMouse,
+    request: Request,
+) -> Result<Reply, Status> {
+    let report = server.handle_report(mouse, &request.encode());
+    let response = Response::decode(&report).unwrap();
+    assert_eq!(response.command, request.command());
+    response.result
+}
+
+#[test]
+fn settings_changes_apply_immediately_and_survive_a_save() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(&sim, SimFlash::new());
+    let mut server = ConfigServer::new([1, 2, 3]);
+
+    assert!(matches!(
+        send(&mut server, &mut mouse, Request::GetDeviceInfo),
+        Ok(Reply::DeviceInfo(info)) if info.firmware_version == [1, 2, 3] && info.button_count == 5
+    ));
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetCpi(1600)),
+        Ok(Reply::Done)
+    );
+    assert_eq!(Config1(sim.register(Register::Config1)).cpi(), 1600);
+    let action = ButtonAction::Consumer(MUTE).to_bytes();
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetButtonAction(3, action)),
+        Ok(Reply::Done)
+    );
+    assert_eq!(
+        send(
+            &mut server,
+            &mut mouse,
+            Request::SetPollingRate(PollingRate::Hz500.into())
+        ),
+        Ok(Reply::Done)
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SaveSettings),
+        Ok(Reply::Done)
+    );
+
+    let (_, settings_store) = mouse.release();
+    let mut mouse = boot(&sim, settings_store.release());
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::GetCpi),
+        Ok(Reply::Cpi(1600))
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::GetButtonAction(3)),
+        Ok(Reply::ButtonAction(3, action))
+    );
+    assert_eq!(mouse.settings().polling_rate, PollingRate::Hz500);
+}
+
+#[test]
+fn invalid_requests_leave_the_settings_alone() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(&sim, SimFlash::new());
+    let mut server = ConfigServer::new([0, 1, 0]);
+
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetCpi(1650)),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::GetButtonAction(5)),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetPollingRate(3)),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(
+            &mut server,
+            &mut mouse,
+            Request::SetLiftConfig(LiftConfig {
+                lift_height: 4,
+                squal_threshold: None,
+            })
+        ),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(
+            &mut server,
+            &mut mouse,
+            Request::SetButtonAction(0, [0xff, 0, 0, 0])
+        ),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetAngleCorrection(1801)),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(
+            &mut server,
+            &mut mouse,
+            Request::SetAngleSnap(AngleSnap {
+                sensor: true,
+                axis_threshold: Some(45),
+            })
+        ),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::SetPowerMode([2; 10])),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(
+        send(
+            &mut server,
+            &mut mouse,
+            Request::ReadRegister(Register::PowerUpReset.address())
+        ),
+        Err(Status::InvalidArgument)
+    );
+    assert_eq!(mouse.settings(), &Settings::DEFAULT);
+    assert_eq!(Config1(sim.register(Register::Config1)).cpi(), 800);
+
+    let mut report = Request::GetCpi.encode();
+    report[0] = PROTOCOL_VERSION + 1;
+    let response = Response::decode(&server.handle_report(&mut mouse, &report)).unwrap();
+    assert_eq!(response.command, Command::GetCpi);
+    assert_eq!(response.result, Err(Status::UnsupportedVersion));
+
+    let response = server.handle_report(&mut mouse, &[PROTOCOL_VERSION, 0x7f, 0, 0]);
+    assert_eq!(response.len(), REPORT_SIZE);
+    assert_eq!(response[1..3], [0x7f, Status::UnknownCommand as u8]);
+}
+
+#[test]
+fn a_captured_frame_is_read_in_chunks_and_navigation_resumes() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(&sim, SimFlash::new());
+    let mut server = ConfigServer::new([0, 1, 0]);
+
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::ReadFrame { offset: 0 }),
+        Err(Status::NoFrame)
+    );
+    let pixels: Vec<u8> = (0..36 * 36).map(|index| (index % 251) as u8).collect();
+    sim.set_image(&pixels);
+    assert_eq!(
+        send(&mut server, &mut mouse, Request::CaptureFrame),
+        Ok(Reply::Done)
+    );
+
+    let mut frame = Vec::new();
+    while frame.len() < pixels.len() {
+        let offset = frame.len() as u16;
+        match send(&mut server, &mut mouse, Request::ReadFrame { offset }) {
+            Ok(Reply::FrameChunk(chunk)) => {
+                assert_eq!(chunk.offset, offset);
+                assert!(chunk.pixels().len() <= FRAME_CHUNK_SIZE);
+                frame.extend_from_slice(chunk.pixels());
+            }
+            result => panic!("Unexpected reply {:?}", result),
+        }
+    }
+    assert_eq!(frame, pixels);
+    assert_eq!(sim.violations(), []);
+
+    assert!(sim.srom_loaded());
+    assert_eq!(Config1(sim.register(Register::Config1)).cpi(), 800);
+}
@@ -0,0 +1,29 @@
+use pmw3360_mouse::cpi_presets::CpiPresets;
+
+#[test]
+fn steps_from_a_preset_to_its_neighbours() {
+    let presets = CpiPresets::default();
+
+    assert_eq!(presets.next(800), 1600);
+    assert_eq!(presets.previous(800), 400);
+}
+
+#[test]
+fn steps_from_any_cpi_to_the_nearest_preset_in_that_direction() {
+    let presets = CpiPresets::default();
+
+    assert_eq!(presets.next(1000), 1600);
+    assert_eq!(presets.previous(1000), 800);
+    assert_eq!(presets.next(100), 400);
+    assert_eq!(presets.previous(12000), 3200);
+}
+
+#[test]
+fn steps_wrap_around_at_either_end() {
+    let presets = CpiPresets::default();
+
+    assert_eq!(presets.next(3200), 400);
+    assert_eq!(presets.next(5000), 400);
+    assert_eq!(presets.previous(400), 3200);
+    assert_eq!(presets.previous(100), 3200);
+}
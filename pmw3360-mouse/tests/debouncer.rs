@@ -0,0 +1,33 @@
+use pmw3360_mouse::button_data::ButtonData;
+use pmw3360_mouse::debouncer::{DebounceAlgorithm, DebounceConfig, Debouncer};
+
+const PRESSED: ButtonData = ButtonData::from_bits(0b1);
+const RELEASED: ButtonData = ButtonData::from_bits(0b0);
+
+fn debouncer(algorithm: DebounceAlgorithm) -> Debouncer<1> {
+    Debouncer::new([DebounceConfig {
+        algorithm,
+        debounce_ms: 5,
+    }])
+}
+
+#[test]
+fn eager_reports_the_press_immediately_and_ignores_bounce() {
+    let mut debouncer = debouncer(DebounceAlgorithm::Eager);
+
+    assert_eq!(debouncer.update(PRESSED, 100), PRESSED);
+    assert_eq!(debouncer.update(RELEASED, 101), PRESSED);
+    assert_eq!(debouncer.update(PRESSED, 102), PRESSED);
+    assert_eq!(debouncer.update(RELEASED, 106), RELEASED);
+}
+
+#[test]
+fn deferred_waits_for_a_stable_level() {
+    let mut debouncer = debouncer(DebounceAlgorithm::Deferred);
+
+    assert_eq!(debouncer.update(PRESSED, 100), RELEASED);
+    assert_eq!(debouncer.update(RELEASED, 102), RELEASED);
+    assert_eq!(debouncer.update(PRESSED, 103), RELEASED);
+    assert_eq!(debouncer.update(PRESSED, 107), RELEASED);
+    assert_eq!(debouncer.update(PRESSED, 108), PRESSED);
+}
@@ -0,0 +1,71 @@
+use pmw3360_mouse::fine_rotation::{split_angle_correction, FineRotation};
+use pmw3360_mouse::motion_data::MotionData;
+
+fn apply(rotation: &mut FineRotation, delta_x: i16, delta_y: i16) -> (i16, i16) {
+    let motion_data = rotation.apply(MotionData {
+        delta_x,
+        delta_y,
+        ..Default::default()
+    });
+    (motion_data.delta_x, motion_data.delta_y)
+}
+
+#[test]
+fn quarter_turns_are_exact() {
+    let mut rotation = FineRotation::new(900);
+
+    assert_eq!(apply(&mut rotation, 3, 5), (5, -3));
+    assert_eq!(apply(&mut rotation, -400, 20), (20, 400));
+    assert_eq!(apply(&mut FineRotation::new(-1800), 3, 5), (-3, -5));
+}
+
+#[test]
+fn zero_angle_passes_motion_through() {
+    let mut rotation = FineRotation::default();
+
+    assert_eq!(
+        apply(&mut rotation, i16::MIN, i16::MAX),
+        (i16::MIN, i16::MAX)
+    );
+}
+
+#[test]
+fn small_angles_carry_sub_count_motion() {
+    // At 0.5° a single count rotates to (0.0087, 0.99996): both round down to zero on their
+    // own, so only the carried remainders make the motion show up.
+    let mut rotation = FineRotation::new(5);
+
+    let (total_x, total_y) = (0..1000).fold((0i32, 0i32), |(total_x, total_y), _| {
+        let (delta_x, delta_y) = apply(&mut rotation, 0, 1);
+        (total_x + delta_x as i32, total_y + delta_y as i32)
+    });
+
+    assert_eq!((total_x, total_y), (8, 999));
+}
+
+#[test]
+fn carried_remainders_keep_reversed_motion_balanced() {
+    let mut rotation = FineRotation::new(-237);
+
+    let forward = (0..500).fold((0i32, 0i32), |(total_x, total_y), _| {
+        let (delta_x, delta_y) = apply(&mut rotation, 3, -2);
+        (total_x + delta_x as i32, total_y + delta_y as i32)
+    });
+    let back = (0..500).fold((0i32, 0i32), |(total_x, total_y), _| {
+        let (delta_x, delta_y) = apply(&mut rotation, -3, 2);
+        (total_x + delta_x as i32, total_y + delta_y as i32)
+    });
+
+    assert_eq!((forward.0 + back.0, forward.1 + back.1), (0, 0));
+}
+
+#[test]
+fn split_leaves_what_angle_tune_cannot_take_to_software() {
+    assert_eq!(split_angle_correction(0), (0, 0));
+    assert_eq!(split_angle_correction(125), (12, 5));
+    assert_eq!(split_angle_correction(-125), (-12, -5));
+    assert_eq!(split_angle_correction(300), (30, 0));
+    assert_eq!(split_angle_correction(305), (30, 5));
+    assert_eq!(split_angle_correction(450), (30, 150));
+    assert_eq!(split_angle_correction(-450), (-30, -150));
+}
@@ -0,0 +1,44 @@
+use pmw3360_mouse::lift_filter::LiftFilter;
+use pmw3360_mouse::motion_data::MotionData;
+use pmw3360_mouse::registers::Motion;
+
+fn frame(motion: u8, squal: u8) -> MotionData {
+    MotionData {
+        delta_x: 12,
+        delta_y: -7,
+        motion: Motion(motion),
+        squal,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn lift_stat_suppresses_motion() {
+    let filter = LiftFilter::new(None);
+
+    let filtered = filter.apply(frame(0x88, 64));
+
+    assert_eq!((filtered.delta_x, filtered.delta_y), (0, 0));
+    assert_eq!(filtered.squal, 64);
+}
+
+#[test]
+fn squal_below_the_threshold_suppresses_motion() {
+    let filter = LiftFilter::new(Some(16));
+
+    let below = filter.apply(frame(0x80, 15));
+    let at = filter.apply(frame(0x80, 16));
+
+    assert_eq!((below.delta_x, below.delta_y), (0, 0));
+    assert_eq!((at.delta_x, at.delta_y), (12, -7));
+}
+
+#[test]
+fn frames_on_the_surface_pass_unchanged() {
+    let filter = LiftFilter::new(Some(16));
+    let on_surface = frame(0x80, 64);
+
+    assert!(!filter.is_lifted(&on_surface));
+    assert_eq!(filter.apply(on_surface), on_surface);
+    assert_eq!(LiftFilter::new(None).apply(frame(0x80, 0)), frame(0x80, 0));
+}
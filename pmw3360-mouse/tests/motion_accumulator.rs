@@ -0,0 +1,64 @@
+use pmw3360_mouse::motion_accumulator::MotionAccumulator;
+use pmw3360_mouse::motion_data::MotionData;
+use pmw3360_mouse::mouse_report::{MAX_REPORT_DELTA, MAX_REPORT_WHEEL};
+
+fn motion(delta_x: i16, delta_y: i16) -> MotionData {
+    MotionData {
+        delta_x,
+        delta_y,
+        ..MotionData::default()
+    }
+}
+
+#[test]
+fn sums_beyond_the_report_range_are_split_exactly() {
+    let mut accumulator = MotionAccumulator::new();
+    for _ in 0..3 {
+        accumulator.add(&motion(30_000, -30_000));
+    }
+    accumulator.add(&motion(1, -1));
+    accumulator.add_wheel(300);
+
+    let (mut total_x, mut total_y, mut total_wheel, mut reports) = (0i32, 0i32, 0i32, 0);
+    while !accumulator.is_empty() {
+        let motion_data = accumulator.pending(MAX_REPORT_DELTA);
+        let wheel = accumulator.pending_wheel(MAX_REPORT_WHEEL);
+        // The report's logical range is symmetric, -32768 must not come out.
+        assert!(motion_data.delta_x >= -MAX_REPORT_DELTA);
+        assert!(motion_data.delta_y >= -MAX_REPORT_DELTA);
+
+        accumulator.consume(&motion_data);
+        accumulator.consume_wheel(wheel);
+        total_x += motion_data.delta_x as i32;
+        total_y += motion_data.delta_y as i32;
+        total_wheel += wheel as i32;
+        reports += 1;
+    }
+
+    assert_eq!((total_x, total_y, total_wheel), (90_001, -90_001, 300));
+    assert_eq!(reports, 3);
+}
+
+#[test]
+fn counts_stay_until_a_report_is_consumed() {
+    let mut accumulator = MotionAccumulator::new();
+    accumulator.add(&motion(5, 7));
+    accumulator.add_wheel(-2);
+
+    // The endpoint was busy: the report is built, but not consumed.
+    let _ = accumulator.pending(MAX_REPORT_DELTA);
+    let _ = accumulator.pending_wheel(MAX_REPORT_WHEEL);
+    accumulator.add(&motion(1, -1));
+    accumulator.add_wheel(-1);
+
+    let motion_data = accumulator.pending(MAX_REPORT_DELTA);
+    let wheel = accumulator.pending_wheel(MAX_REPORT_WHEEL);
+    assert_eq!(
+        (motion_data.delta_x, motion_data.delta_y, wheel),
+        (6, 6, -3)
+    );
+
+    accumulator.consume(&motion_data);
+    accumulator.consume_wheel(wheel);
+    assert!(accumulator.is_empty());
+}
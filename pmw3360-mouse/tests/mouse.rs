@@ -0,0 +1,76 @@
+use pmw3360_mouse::mouse::Mouse;
+use pmw3360_mouse::pmw_driver::PmwDriver;
+use pmw3360_mouse::registers::{Config1, Register};
+use pmw3360_mouse::settings::Settings;
+use pmw3360_mouse::settings_store::SettingsStore;
+use pmw3360_mouse::sim::{Pmw3360Sim, SimChipEnable, SimDelay, SimMotion, SimSpi};
+use pmw3360_mouse::sim_flash::SimFlash;
+
+type SimMouse = Mouse<SimSpi, SimChipEnable, SimDelay, SimFlash, 5>;
+
+fn boot(sim: &Pmw3360Sim, settings: Settings) -> SimMouse {
+    let mut pmw_driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    let sensor_info = pmw_driver.init().unwrap();
+    let settings_store = SettingsStore::new(SimFlash::new());
+    let mut mouse = Mouse::new(pmw_driver, sensor_info, settings_store, settings);
+    mouse.configure_sensor().unwrap();
+    mouse
+}
+
+#[test]
+fn cpi_steps_start_from_the_configured_cpi() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(
+        &sim,
+        Settings {
+            cpi: 3200,
+            ..Settings::DEFAULT
+        },
+    );
+
+    mouse.step_cpi(-1).unwrap();
+
+    assert_eq!(mouse.settings().cpi, 1600);
+    assert_eq!(Config1(sim.register(Register::Config1)).cpi(), 1600);
+}
+
+#[test]
+fn cpi_steps_follow_settings_changes() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(&sim, Settings::DEFAULT);
+    mouse
+        .set_settings(Settings {
+            cpi: 1000,
+            ..Settings::DEFAULT
+        })
+        .unwrap();
+
+    mouse.step_cpi(1).unwrap();
+    assert_eq!(mouse.settings().cpi, 1600);
+
+    mouse.step_cpi(-2).unwrap();
+    assert_eq!(mouse.settings().cpi, 400);
+    assert_eq!(Config1(sim.register(Register::Config1)).cpi(), 400);
+}
+
+#[test]
+fn register_reads_leave_pending_motion_alone() {
+    let sim = Pmw3360Sim::new();
+    let mut mouse = boot(&sim, Settings::DEFAULT);
+    sim.queue_motion(SimMotion::new(5, -3));
+
+    for register in [
+        Register::Motion,
+        Register::DeltaXL,
+        Register::DeltaXH,
+        Register::DeltaYL,
+        Register::DeltaYH,
+        Register::MotionBurst,
+    ] {
+        assert!(mouse.read_register(register.address()).is_none());
+    }
+    assert!(mouse.read_register(Register::Squal.address()).is_some());
+
+    let motion_data = mouse.read_motion().unwrap();
+    assert_eq!((motion_data.delta_x, motion_data.delta_y), (5, -3));
+}
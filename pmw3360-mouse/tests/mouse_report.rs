@@ -0,0 +1,40 @@
+use pmw3360_mouse::button_data::ButtonData;
+use pmw3360_mouse::motion_data::MotionData;
+use pmw3360_mouse::mouse_report::{BootMouseReport, MouseReport, MAX_REPORT_DELTA};
+
+fn motion(delta_x: i16, delta_y: i16) -> MotionData {
+    MotionData {
+        delta_x,
+        delta_y,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn fields_are_laid_out_little_endian() {
+    let report = MouseReport::new(motion(0x1234, -2), ButtonData::from_bits(0x8105), -5);
+
+    assert_eq!(report.as_ref(), [0x05, 0x81, 0x34, 0x12, 0xfe, 0xff, 0xfb]);
+}
+
+#[test]
+fn deltas_and_wheel_are_clamped_to_the_logical_range() {
+    let report = MouseReport::new(motion(i16::MIN, i16::MAX), ButtonData::default(), i8::MIN);
+
+    let [_, _, x_low, x_high, y_low, y_high, wheel] = report.as_ref().try_into().unwrap();
+    assert_eq!(i16::from_le_bytes([x_low, x_high]), -MAX_REPORT_DELTA);
+    assert_eq!(i16::from_le_bytes([y_low, y_high]), MAX_REPORT_DELTA);
+    assert_eq!(wheel as i8, -127);
+}
+
+#[test]
+fn boot_report_saturates_deltas_to_8_bits() {
+    let report = BootMouseReport::new(motion(300, -300), ButtonData::from_bits(0b11010));
+
+    // Only left, right and middle fit, and the deltas stop at ±127 instead of wrapping.
+    assert_eq!(report.as_ref(), [0b010, 0x7f, 0x81]);
+    assert_eq!(
+        BootMouseReport::new(motion(i16::MIN, 5), ButtonData::default()).as_ref(),
+        [0, 0x81, 0x05]
+    );
+}
@@ -0,0 +1,70 @@
+use pmw3360_mouse::motion_data::MotionData;
+use pmw3360_mouse::orientation::{Orientation, Rotation};
+
+fn apply(orientation: Orientation, delta_x: i16, delta_y: i16) -> (i16, i16) {
+    let motion_data = orientation.apply(MotionData {
+        delta_x,
+        delta_y,
+        ..Default::default()
+    });
+    (motion_data.delta_x, motion_data.delta_y)
+}
+
+fn rotated(rotation: Rotation) -> Orientation {
+    Orientation {
+        rotation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rotations_turn_counter_clockwise() {
+    assert_eq!(apply(rotated(Rotation::Deg0), 3, 5), (3, 5));
+    assert_eq!(apply(rotated(Rotation::Deg90), 3, 5), (-5, 3));
+    assert_eq!(apply(rotated(Rotation::Deg180), 3, 5), (-3, -5));
+    assert_eq!(apply(rotated(Rotation::Deg270), 3, 5), (5, -3));
+}
+
+#[test]
+fn swap_and_invert_apply_after_rotation() {
+    let swapped = Orientation {
+        swap_xy: true,
+        ..Default::default()
+    };
+    let inverted_x = Orientation {
+        invert_x: true,
+        ..Default::default()
+    };
+    let inverted_y = Orientation {
+        invert_y: true,
+        ..Default::default()
+    };
+    let combined = Orientation {
+        rotation: Rotation::Deg90,
+        swap_xy: true,
+        invert_x: true,
+        invert_y: false,
+    };
+
+    assert_eq!(apply(swapped, 3, 5), (5, 3));
+    assert_eq!(apply(inverted_x, 3, 5), (-3, 5));
+    assert_eq!(apply(inverted_y, 3, 5), (3, -5));
+    assert_eq!(apply(combined, 3, 5), (-3, -5));
+}
+
+#[test]
+fn negating_i16_min_saturates() {
+    let inverted = Orientation {
+        invert_x: true,
+        invert_y: true,
+        ..Default::default()
+    };
+
+    assert_eq!(apply(rotated(Rotation::Deg90), 0, i16::MIN), (i16::MAX, 0));
+    assert_eq!(
+        apply(rotated(Rotation::Deg180), i16::MIN, i16::MIN),
+        (i16::MAX, i16::MAX)
+    );
+    assert_eq!(apply(rotated(Rotation::Deg270), i16::MIN, 0), (0, i16::MAX));
+    assert_eq!(apply(inverted, i16::MIN, i16::MIN), (i16::MAX, i16::MAX));
+}
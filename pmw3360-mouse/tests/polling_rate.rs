@@ -0,0 +1,21 @@
+use pmw3360_mouse::polling_rate::{PollingRate, ReportTimer};
+
+#[test]
+fn report_timer_fires_once_per_interval() {
+    let mut timer = ReportTimer::new(PollingRate::Hz250, u32::MAX - 5);
+
+    let due: Vec<u32> = (0..16)
+        .map(|step| (u32::MAX - 5).wrapping_add(step))
+        .filter(|&now_ms| timer.is_due(now_ms))
+        .collect();
+    assert_eq!(due, [u32::MAX - 5, u32::MAX - 1, 2, 6]);
+
+    // After a stall the schedule restarts from now instead of bursting.
+    assert!(timer.is_due(100));
+    assert!(!timer.is_due(101));
+    assert!(timer.is_due(104));
+
+    assert_eq!(PollingRate::Hz125.hz(), 125);
+    assert_eq!(PollingRate::try_from(1), Ok(PollingRate::Hz1000));
+    assert_eq!(PollingRate::try_from(3), Err(3));
+}
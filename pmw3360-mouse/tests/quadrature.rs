@@ -0,0 +1,23 @@
+use pmw3360_mouse::quadrature::{QuadratureDecoder, StepsPerDetent};
+
+const FORWARD: [(bool, bool); 4] = [(true, false), (true, true), (false, true), (false, false)];
+
+#[test]
+fn full_cycles_count_as_detents_and_bounce_cancels() {
+    let mut decoder = QuadratureDecoder::new(StepsPerDetent::Four, false, false);
+
+    for (a, b) in FORWARD.into_iter().chain(FORWARD) {
+        decoder.update(a, b);
+    }
+    assert_eq!(decoder.take_detents(), 2);
+
+    // Contact A chattering at the start of a detent, then the wheel turning back.
+    for (a, b) in [(true, false), (false, false), (true, false), (false, false)] {
+        decoder.update(a, b);
+    }
+    for (a, b) in FORWARD.into_iter().rev().skip(1).chain([(false, false)]) {
+        decoder.update(a, b);
+    }
+    assert_eq!(decoder.take_detents(), -1);
+    assert_eq!(decoder.take_detents(), 0);
+}
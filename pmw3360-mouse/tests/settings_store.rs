@@ -0,0 +1,75 @@
+use pmw3360_mouse::button_map::ButtonAction;
+use pmw3360_mouse::orientation::Rotation;
+use pmw3360_mouse::settings::{Settings, SETTINGS_SIZE};
+use pmw3360_mouse::settings_store::SettingsStore;
+use pmw3360_mouse::sim_flash::SimFlash;
+
+fn settings(cpi: u16) -> Settings {
+    let mut settings = Settings::DEFAULT;
+    settings.cpi = cpi;
+    settings.orientation.rotation = Rotation::Deg180;
+    settings.button_actions[3] = ButtonAction::CpiUp;
+    settings
+}
+
+#[test]
+fn saved_settings_are_loaded_back() {
+    let mut store = SettingsStore::new(SimFlash::new());
+    assert_eq!(store.load(), Ok(None));
+
+    store.save(&settings(1600)).unwrap();
+    store.save(&settings(3200)).unwrap();
+
+    let mut store = SettingsStore::new(store.release());
+    assert_eq!(store.load(), Ok(Some(settings(3200))));
+}
+
+#[test]
+fn saves_rotate_through_all_pages() {
+    let mut store = SettingsStore::new(SimFlash::new());
+    store.load().unwrap();
+
+    for cpi in 1..=100 {
+        store.save(&settings(cpi * 100)).unwrap();
+    }
+
+    let mut store = SettingsStore::new(store.release());
+    assert_eq!(store.load(), Ok(Some(settings(10000))));
+    let flash = store.release();
+    // 15 records per page: the 100 saves went around the region once and on into the third page.
+    assert_eq!(flash.erase_counts(), [2, 2, 2, 1]);
+}
+
+#[test]
+fn an_interrupted_save_keeps_the_previous_settings() {
+    let mut store = SettingsStore::new(SimFlash::new());
+    store.load().unwrap();
+    store.save(&settings(800)).unwrap();
+
+    let mut flash = store.release();
+    flash.lose_power_after(20);
+    let mut store = SettingsStore::new(flash);
+    store.load().unwrap();
+    assert!(store.save(&settings(1600)).is_err());
+
+    let mut flash = store.release();
+    flash.restore_power();
+    let mut store = SettingsStore::new(flash);
+    assert_eq!(store.load(), Ok(Some(settings(800))));
+
+    // The half-written slot is skipped by the next save.
+    store.save(&settings(2400)).unwrap();
+    let mut store = SettingsStore::new(store.release());
+    assert_eq!(store.load(), Ok(Some(settings(2400))));
+}
+
+#[test]
+fn version_1_settings_are_upgraded() {
+    let bytes = settings(1600).to_bytes();
+
+    // Version 1 ended before the polling rate, which then gets its default.
+    let upgraded = Settings::from_bytes(1, &bytes[..SETTINGS_SIZE - 1]);
+    assert_eq!(upgraded, Some(settings(1600)));
+    assert_eq!(Settings::from_bytes(1, &bytes), None);
+    assert_eq!(Settings::from_bytes(3, &bytes), None);
+}
@@ -0,0 +1,250 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::spi::SpiDevice;
+use pmw3360_mouse::pmw_driver::{PmwDriver, PmwError, SromError};
+use pmw3360_mouse::power_mode::PowerMode;
+use pmw3360_mouse::registers::{AngleTune, Config2, LiftHeight, Register};
+use pmw3360_mouse::sim::{Pmw3360Sim, SimMotion, Violation};
+
+#[test]
+fn init_downloads_srom_within_datasheet_timing() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+
+    driver.init().unwrap();
+
+    assert!(sim.srom_loaded());
+    assert!(!Config2(sim.register(Register::Config2)).rest_en());
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn init_retries_a_corrupted_srom_download() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+
+    sim.corrupt_srom_downloads(2);
+    let sensor_info = driver.init().unwrap();
+    assert_eq!(sensor_info.srom_id, 0x04);
+    assert_eq!(sim.violations(), []);
+
+    sim.corrupt_srom_downloads(3);
+    assert_eq!(
+        driver.init(),
+        Err(PmwError::SromFailed(SromError::BadCrc(0x0000)))
+    );
+}
+
+#[test]
+fn motion_burst_serves_scripted_frames() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    sim.queue_motion(SimMotion::new(3, -4));
+    sim.queue_motion(SimMotion::new(-5, 6));
+    let first = driver.read_motion().unwrap();
+    let second = driver.read_motion().unwrap();
+    let idle = driver.read_motion().unwrap();
+
+    assert_eq!((first.delta_x, first.delta_y), (3, -4));
+    assert_eq!((first.squal, first.shutter), (0x40, 0x0012));
+    assert!(!first.lifted());
+    assert_eq!((second.delta_x, second.delta_y), (-5, 6));
+    assert_eq!((idle.delta_x, idle.delta_y), (0, 0));
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn register_reads_end_burst_mode() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    sim.queue_motion(SimMotion::new(3, -4));
+    sim.queue_motion(SimMotion::new(-5, 6));
+    driver.read_motion().unwrap();
+    driver.get_cpi().unwrap();
+    let second = driver.read_motion().unwrap();
+
+    assert_eq!((second.delta_x, second.delta_y), (-5, 6));
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn motion_pin_is_low_until_pending_motion_was_read() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    let mut motion_pin = sim.motion_pin();
+    sim.queue_motion(SimMotion::new(1, 1));
+    assert!(motion_pin.is_high().unwrap());
+
+    // Init clears the motion registers, taking the frame queued before.
+    driver.init().unwrap();
+    assert!(motion_pin.is_high().unwrap());
+    sim.queue_motion(SimMotion::new(1, 1));
+    assert!(motion_pin.is_low().unwrap());
+    driver.read_motion().unwrap();
+    assert!(motion_pin.is_high().unwrap());
+
+    sim.queue_motion(SimMotion::new(0, 0));
+    assert!(motion_pin.is_high().unwrap());
+}
+
+#[test]
+fn reading_data_before_t_srad_is_reported() {
+    let sim = Pmw3360Sim::new();
+    let mut spi = sim.spi();
+    let mut chip_enable = sim.chip_enable();
+
+    chip_enable.set_low().unwrap();
+    let mut frame = [0x00, 0xff];
+    spi.transfer_in_place(&mut frame).unwrap();
+    chip_enable.set_high().unwrap();
+
+    assert!(matches!(
+        sim.violations()[..],
+        [Violation::ReadAddressDataDelay { address: 0x00, .. }]
+    ));
+}
+
+#[test]
+fn identify_rejects_a_different_sensor() {
+    let sim = Pmw3360Sim::new();
+    sim.set_register(Register::ProductId, 0x47);
+    sim.set_register(Register::InverseProductId, !0x47);
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+
+    assert_eq!(
+        driver.identify(),
+        Err(PmwError::WrongSensor {
+            product_id: 0x47,
+            inverse_product_id: !0x47,
+        })
+    );
+}
+
+#[test]
+fn bus_errors_are_returned_and_reads_recover() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    sim.fail_transactions(1);
+    assert!(matches!(driver.read_motion(), Err(PmwError::Bus(_))));
+
+    sim.queue_motion(SimMotion::new(1, 1));
+    let motion_data = driver.read_motion().unwrap();
+    assert_eq!((motion_data.delta_x, motion_data.delta_y), (1, 1));
+}
+
+#[test]
+fn power_mode_presets_are_written_and_read_back() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+    assert_eq!(driver.get_power_mode(), Ok(PowerMode::PERFORMANCE));
+
+    driver.set_power_mode(&PowerMode::POWER_SAVER).unwrap();
+    assert!(Config2(sim.register(Register::Config2)).rest_en());
+    assert_eq!(sim.register(Register::Rest3RateUpper), 0x01);
+    assert_eq!(driver.get_power_mode(), Ok(PowerMode::POWER_SAVER));
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn capture_frame_returns_the_raw_image() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    let pixels: Vec<u8> = (0..36 * 36).map(|index| (index / 36) as u8).collect();
+    sim.set_image(&pixels);
+    let image = driver.capture_frame().unwrap();
+
+    assert_eq!(image.pixels(), &pixels[..]);
+    assert_eq!(image.pixel(0, 35), 35);
+    assert!(image.to_pgm().starts_with(b"P5\n36 36\n255\n"));
+    assert_eq!(sim.violations(), []);
+
+    assert!(!sim.srom_loaded());
+    driver.init().unwrap();
+    assert!(sim.srom_loaded());
+}
+
+#[test]
+fn set_cpi_rejects_unsupported_values_without_writing() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+    let config1 = sim.register(Register::Config1);
+
+    for cpi in [0, 50, 150, 12100] {
+        assert_eq!(driver.set_cpi(cpi), Err(PmwError::UnsupportedCpi(cpi)));
+        assert_eq!(sim.register(Register::Config1), config1);
+    }
+
+    for (cpi, value) in [(100, 0x00), (12000, 0x77)] {
+        driver.set_cpi(cpi).unwrap();
+        assert_eq!(sim.register(Register::Config1), value);
+        assert_eq!(driver.get_cpi(), Ok(cpi));
+    }
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn set_lift_height_writes_lift_config() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    driver.set_lift_height(LiftHeight::Mm3).unwrap();
+    assert_eq!(sim.register(Register::LiftConfig), 0x03);
+    assert_eq!(driver.get_lift_height(), Ok(LiftHeight::Mm3));
+
+    driver.set_lift_height(LiftHeight::Mm2).unwrap();
+    assert_eq!(sim.register(Register::LiftConfig), 0x02);
+    assert_eq!(driver.get_lift_height(), Ok(LiftHeight::Mm2));
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn set_angle_tune_rejects_angles_beyond_30_degrees() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+
+    for degrees in [31, -31, i8::MAX, i8::MIN] {
+        assert_eq!(
+            driver.set_angle_tune(degrees),
+            Err(PmwError::UnsupportedAngle(degrees))
+        );
+        assert_eq!(sim.register(Register::AngleTune), 0);
+    }
+
+    for degrees in [30, -30] {
+        driver.set_angle_tune(degrees).unwrap();
+        assert_eq!(
+            sim.register(Register::AngleTune),
+            AngleTune::from_angle(degrees).0
+        );
+        assert_eq!(driver.get_angle_tune(), Ok(degrees));
+    }
+    assert_eq!(sim.violations(), []);
+}
+
+#[test]
+fn set_angle_snap_toggles_only_bit_7() {
+    let sim = Pmw3360Sim::new();
+    let mut driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+    driver.init().unwrap();
+    sim.set_register(Register::AngleSnap, 0x05);
+
+    driver.set_angle_snap(true).unwrap();
+    assert_eq!(sim.register(Register::AngleSnap), 0x85);
+    assert_eq!(driver.get_angle_snap(), Ok(true));
+
+    driver.set_angle_snap(false).unwrap();
+    assert_eq!(sim.register(Register::AngleSnap), 0x05);
+    assert_eq!(driver.get_angle_snap(), Ok(false));
+    assert_eq!(sim.violations(), []);
+}
@@ -0,0 +1,19 @@
+//! Vendor-defined HID protocol for configuring the mouse from the host.
+//!
+//! The host writes a `Request` as an output report on the configuration interface and reads the
+//! matching `Response` from its input report. Both are `REPORT_SIZE` bytes:
+//!
+//! | offset | size | field                                            |
+//! |--------|------|--------------------------------------------------|
+//! | 0      | 1    | `PROTOCOL_VERSION`                               |
+//! | 1      | 1    | `Command`                                        |
+//! | 2      | 1    | `Status`, 0 in requests                          |
+//! | 3      | 1    | payload length                                   |
+//! | 4      | 60   | payload, multi-byte fields little endian         |
+//!
+//! Only the messages and their encoding live here, shared by the firmware and host tools. Field
+//! values use the same encodings as settings records.
+
+#![no_std]
+
+pub mod message;
@@ -0,0 +1,564 @@
+/// Bumped whenever the meaning of an existing command or payload changes.
+pub const PROTOCOL_VERSION: u8 = 1;
+/// Size of every request and response, one full-speed interrupt packet.
+pub const REPORT_SIZE: usize = 64;
+pub const HEADER_SIZE: usize = 4;
+pub const MAX_PAYLOAD_SIZE: usize = REPORT_SIZE - HEADER_SIZE;
+/// Image bytes per `Reply::FrameChunk`; 27 chunks make up a frame.
+pub const FRAME_CHUNK_SIZE: usize = 48;
+/// Size of an encoded button action: a tag byte followed by the action's parameters, as in
+/// settings records.
+pub const BUTTON_ACTION_SIZE: usize = 4;
+/// Size of an encoded power mode: Config2 REST_EN followed by the rest rate and downshift
+/// registers, as in settings records.
+pub const POWER_MODE_SIZE: usize = 10;
+
+/// Vendor-defined usage page of the configuration interface, for host tools to find it by.
+pub const USAGE_PAGE: u16 = 0xff00;
+/// Report descriptor of the configuration interface: one 64-byte input report for responses
+/// and one 64-byte output report for requests, without report IDs.
+pub const REPORT_DESCRIPTOR: &[u8] = &[
+    0x06, 0x00, 0xff, // USAGE_PAGE Vendor Defined 0xff00
+    0x09, 0x01, // USAGE 1
+    0xa1, 0x01, // COLLECTION Application
+    0x15, 0x00, // LOGICAL_MINIMUM 0
+    0x26, 0xff, 0x00, // LOGICAL_MAXIMUM 255
+    0x75, 0x08, // REPORT_SIZE 8
+    0x95, 0x40, // REPORT_COUNT 64
+    0x09, 0x01, // USAGE 1
+    0x81, 0x02, // INPUT Data,Var,Abs
+    0x95, 0x40, // REPORT_COUNT 64
+    0x09, 0x01, // USAGE 1
+    0x91, 0x02, // OUTPUT Data,Var,Abs
+    0xc0, // END COLLECTION
+];
+
+// Encodes `None` for the optional thresholds, as in `Settings::to_bytes`.
+const NONE: u8 = 0xff;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    GetDeviceInfo = 0x01,
+    GetCpi = 0x10,
+    SetCpi = 0x11,
+    GetOrientation = 0x12,
+    SetOrientation = 0x13,
+    GetPollingRate = 0x14,
+    SetPollingRate = 0x15,
+    GetLiftConfig = 0x16,
+    SetLiftConfig = 0x17,
+    GetButtonAction = 0x18,
+    SetButtonAction = 0x19,
+    GetAngleCorrection = 0x1a,
+    SetAngleCorrection = 0x1b,
+    GetAngleSnap = 0x1c,
+    SetAngleSnap = 0x1d,
+    GetPowerMode = 0x1e,
+    SetPowerMode = 0x1f,
+    SaveSettings = 0x20,
+    ReadRegister = 0x30,
+    CaptureFrame = 0x31,
+    ReadFrame = 0x32,
+}
+
+impl Command {
+    pub const ALL: [Command; 21] = [
+        Command::GetDeviceInfo,
+        Command::GetCpi,
+        Command::SetCpi,
+        Command::GetOrientation,
+        Command::SetOrientation,
+        Command::GetPollingRate,
+        Command::SetPollingRate,
+        Command::GetLiftConfig,
+        Command::SetLiftConfig,
+        Command::GetButtonAction,
+        Command::SetButtonAction,
+        Command::GetAngleCorrection,
+        Command::SetAngleCorrection,
+        Command::GetAngleSnap,
+        Command::SetAngleSnap,
+        Command::GetPowerMode,
+        Command::SetPowerMode,
+        Command::SaveSettings,
+        Command::ReadRegister,
+        Command::CaptureFrame,
+        Command::ReadFrame,
+    ];
+}
+
+impl From<Command> for u8 {
+    fn from(command: Command) -> Self {
+        command as u8
+    }
+}
+
+impl TryFrom<u8> for Command {
+    type Error = u8;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|&command| command as u8 == code)
+            .ok_or(code)
+    }
+}
+
+/// Outcome of a request, other than success.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// The request was for another `PROTOCOL_VERSION`.
+    UnsupportedVersion = 0x01,
+    UnknownCommand = 0x02,
+    /// The payload is malformed or a value is out of range.
+    InvalidArgument = 0x03,
+    /// Talking to the sensor failed.
+    SensorError = 0x04,
+    /// Saving to flash failed.
+    FlashError = 0x05,
+    /// `ReadFrame` before a successful `CaptureFrame`.
+    NoFrame = 0x06,
+}
+
+impl Status {
+    pub const ALL: [Status; 6] = [
+        Status::UnsupportedVersion,
+        Status::UnknownCommand,
+        Status::InvalidArgument,
+        Status::SensorError,
+        Status::FlashError,
+        Status::NoFrame,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Shorter than the header, or than the payload length it declares.
+    Truncated,
+    UnsupportedVersion(u8),
+    UnknownCommand(u8),
+    UnknownStatus(u8),
+    /// The payload doesn't fit the command.
+    InvalidPayload,
+}
+
+impl From<DecodeError> for Status {
+    fn from(error: DecodeError) -> Self {
+        match error {
+            DecodeError::UnsupportedVersion(_) => Status::UnsupportedVersion,
+            DecodeError::UnknownCommand(_) => Status::UnknownCommand,
+            _ => Status::InvalidArgument,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Major, minor and patch version of the firmware.
+    pub firmware_version: [u8; 3],
+    pub product_id: u8,
+    pub revision_id: u8,
+    pub srom_id: u8,
+    /// Number of physical buttons, the valid range of button indices.
+    pub button_count: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiftConfig {
+    /// Lift-off distance in millimetres.
+    pub lift_height: u8,
+    /// SQUAL below which motion is dropped in software, see `LiftFilter`.
+    pub squal_threshold: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AngleSnap {
+    /// The sensor's own snapping to the X and Y axes.
+    pub sensor: bool,
+    /// Threshold of the software snapping in degrees, see `AxisSnap`.
+    pub axis_threshold: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameChunk {
+    /// Index of the first pixel in the chunk.
+    pub offset: u16,
+    pub len: u8,
+    pub pixels: [u8; FRAME_CHUNK_SIZE],
+}
+
+impl FrameChunk {
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels[..self.len as usize]
+    }
+}
+
+/// Sent from the host to the mouse. Changes apply immediately and are kept until the next reset,
+/// unless followed by `SaveSettings`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Request {
+    GetDeviceInfo,
+    GetCpi,
+    SetCpi(u16),
+    GetOrientation,
+    /// Bits 0..1 rotation in quarter turns, bit 2 swap X/Y, bit 3 invert X, bit 4 invert Y.
+    SetOrientation(u8),
+    GetPollingRate,
+    /// Report interval in milliseconds. Only takes effect after `SaveSettings` and a reset, the
+    /// rate is part of the USB descriptors.
+    SetPollingRate(u8),
+    GetLiftConfig,
+    SetLiftConfig(LiftConfig),
+    GetButtonAction(u8),
+    SetButtonAction(u8, [u8; BUTTON_ACTION_SIZE]),
+    GetAngleCorrection,
+    /// Mounting angle correction in tenths of a degree.
+    SetAngleCorrection(i16),
+    GetAngleSnap,
+    SetAngleSnap(AngleSnap),
+    GetPowerMode,
+    SetPowerMode([u8; POWER_MODE_SIZE]),
+    SaveSettings,
+    /// Reads a sensor register by address, for diagnostics.
+    ReadRegister(u8),
+    /// Captures a raw sensor image, to be read with `ReadFrame`.
+    CaptureFrame,
+    ReadFrame {
+        offset: u16,
+    },
+}
+
+impl Request {
+    pub fn command(&self) -> Command {
+        match self {
+            Request::GetDeviceInfo => Command::GetDeviceInfo,
+            Request::GetCpi => Command::GetCpi,
+            Request::SetCpi(_) => Command::SetCpi,
+            Request::GetOrientation => Command::GetOrientation,
+            Request::SetOrientation(_) => Command::SetOrientation,
+            Request::GetPollingRate => Command::GetPollingRate,
+            Request::SetPollingRate(_) => Command::SetPollingRate,
+            Request::GetLiftConfig => Command::GetLiftConfig,
+            Request::SetLiftConfig(_) => Command::SetLiftConfig,
+            Request::GetButtonAction(_) => Command::GetButtonAction,
+            Request::SetButtonAction(..) => Command::SetButtonAction,
+            Request::GetAngleCorrection => Command::GetAngleCorrection,
+            Request::SetAngleCorrection(_) => Command::SetAngleCorrection,
+            Request::GetAngleSnap => Command::GetAngleSnap,
+            Request::SetAngleSnap(_) => Command::SetAngleSnap,
+            Request::GetPowerMode => Command::GetPowerMode,
+            Request::SetPowerMode(_) => Command::SetPowerMode,
+            Request::SaveSettings => Command::SaveSettings,
+            Request::ReadRegister(_) => Command::ReadRegister,
+            Request::CaptureFrame => Command::CaptureFrame,
+            Request::ReadFrame { .. } => Command::ReadFrame,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; REPORT_SIZE] {
+        let mut payload = Payload::new();
+        match *self {
+            Request::SetCpi(cpi) => payload.push(&cpi.to_le_bytes()),
+            Request::SetOrientation(orientation) => payload.push(&[orientation]),
+            Request::SetPollingRate(interval_ms) => payload.push(&[interval_ms]),
+            Request::SetLiftConfig(lift_config) => encode_lift_config(&mut payload, lift_config),
+            Request::GetButtonAction(button) => payload.push(&[button]),
+            Request::SetButtonAction(button, action) => {
+                payload.push(&[button]);
+                payload.push(&action);
+            }
+            Request::SetAngleCorrection(decidegrees) => payload.push(&decidegrees.to_le_bytes()),
+            Request::SetAngleSnap(angle_snap) => encode_angle_snap(&mut payload, angle_snap),
+            Request::SetPowerMode(power_mode) => payload.push(&power_mode),
+            Request::ReadRegister(address) => payload.push(&[address]),
+            Request::ReadFrame { offset } => payload.push(&offset.to_le_bytes()),
+            Request::GetDeviceInfo
+            | Request::GetCpi
+            | Request::GetOrientation
+            | Request::GetPollingRate
+            | Request::GetLiftConfig
+            | Request::GetAngleCorrection
+            | Request::GetAngleSnap
+            | Request::GetPowerMode
+            | Request::SaveSettings
+            | Request::CaptureFrame => {}
+        }
+        encode_report(self.command() as u8, 0, &payload)
+    }
+
+    pub fn decode(report: &[u8]) -> Result<Self, DecodeError> {
+        let (command, _, payload) = decode_report(report)?;
+        let command = Command::try_from(command).map_err(DecodeError::UnknownCommand)?;
+
+        let request = match command {
+            Command::GetDeviceInfo => Request::GetDeviceInfo,
+            Command::GetCpi => Request::GetCpi,
+            Command::SetCpi => Request::SetCpi(u16::from_le_bytes(fixed(payload)?)),
+            Command::GetOrientation => Request::GetOrientation,
+            Command::SetOrientation => Request::SetOrientation(fixed::<1>(payload)?[0]),
+            Command::GetPollingRate => Request::GetPollingRate,
+            Command::SetPollingRate => Request::SetPollingRate(fixed::<1>(payload)?[0]),
+            Command::GetLiftConfig => Request::GetLiftConfig,
+            Command::SetLiftConfig => Request::SetLiftConfig(decode_lift_config(payload)?),
+            Command::GetButtonAction => Request::GetButtonAction(fixed::<1>(payload)?[0]),
+            Command::SetButtonAction => {
+                let (button, action) = decode_button_action(payload)?;
+                Request::SetButtonAction(button, action)
+            }
+            Command::GetAngleCorrection => Request::GetAngleCorrection,
+            Command::SetAngleCorrection => {
+                Request::SetAngleCorrection(i16::from_le_bytes(fixed(payload)?))
+            }
+            Command::GetAngleSnap => Request::GetAngleSnap,
+            Command::SetAngleSnap => Request::SetAngleSnap(decode_angle_snap(payload)?),
+            Command::GetPowerMode => Request::GetPowerMode,
+            Command::SetPowerMode => Request::SetPowerMode(fixed(payload)?),
+            Command::SaveSettings => Request::SaveSettings,
+            Command::ReadRegister => Request::ReadRegister(fixed::<1>(payload)?[0]),
+            Command::CaptureFrame => Request::CaptureFrame,
+            Command::ReadFrame => Request::ReadFrame {
+                offset: u16::from_le_bytes(fixed(payload)?),
+            },
+        };
+        Ok(request)
+    }
+}
+
+/// Payload of a successful response, depending on the command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reply {
+    /// For commands that don't return anything.
+    Done,
+    DeviceInfo(DeviceInfo),
+    Cpi(u16),
+    /// Encoded as in `Request::SetOrientation`.
+    Orientation(u8),
+    /// Report interval in milliseconds.
+    PollingRate(u8),
+    LiftConfig(LiftConfig),
+    ButtonAction(u8, [u8; BUTTON_ACTION_SIZE]),
+    /// Tenths of a degree.
+    AngleCorrection(i16),
+    AngleSnap(AngleSnap),
+    PowerMode([u8; POWER_MODE_SIZE]),
+    Register {
+        address: u8,
+        value: u8,
+    },
+    FrameChunk(FrameChunk),
+}
+
+/// Sent from the mouse to the host in answer to a request with the same command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Response {
+    pub command: Command,
+    pub result: Result<Reply, Status>,
+}
+
+impl Response {
+    pub fn encode(&self) -> [u8; REPORT_SIZE] {
+        let reply = match self.result {
+            Ok(reply) => reply,
+            Err(status) => return encode_error(self.command as u8, status),
+        };
+
+        let mut payload = Payload::new();
+        match reply {
+            Reply::Done => {}
+            Reply::DeviceInfo(info) => {
+                payload.push(&info.firmware_version);
+                payload.push(&[
+                    info.product_id,
+                    info.revision_id,
+                    info.srom_id,
+                    info.button_count,
+                ]);
+            }
+            Reply::Cpi(cpi) => payload.push(&cpi.to_le_bytes()),
+            Reply::Orientation(orientation) => payload.push(&[orientation]),
+            Reply::PollingRate(interval_ms) => payload.push(&[interval_ms]),
+            Reply::LiftConfig(lift_config) => encode_lift_config(&mut payload, lift_config),
+            Reply::ButtonAction(button, action) => {
+                payload.push(&[button]);
+                payload.push(&action);
+            }
+            Reply::AngleCorrection(decidegrees) => payload.push(&decidegrees.to_le_bytes()),
+            Reply::AngleSnap(angle_snap) => encode_angle_snap(&mut payload, angle_snap),
+            Reply::PowerMode(power_mode) => payload.push(&power_mode),
+            Reply::Register { address, value } => payload.push(&[address, value]),
+            Reply::FrameChunk(chunk) => {
+                payload.push(&chunk.offset.to_le_bytes());
+                payload.push(chunk.pixels());
+            }
+        }
+        encode_report(self.command as u8, 0, &payload)
+    }
+
+    pub fn decode(report: &[u8]) -> Result<Self, DecodeError> {
+        let (command, status, payload) = decode_report(report)?;
+        let command = Command::try_from(command).map_err(DecodeError::UnknownCommand)?;
+        if status != 0 {
+            let status = Status::ALL
+                .into_iter()
+                .find(|&known| known as u8 == status)
+                .ok_or(DecodeError::UnknownStatus(status))?;
+            return Ok(Self {
+                command,
+                result: Err(status),
+            });
+        }
+
+        let reply = match command {
+            Command::GetDeviceInfo => {
+                let bytes: [u8; 7] = fixed(payload)?;
+                Reply::DeviceInfo(DeviceInfo {
+                    firmware_version: [bytes[0], bytes[1], bytes[2]],
+                    product_id: bytes[3],
+                    revision_id: bytes[4],
+                    srom_id: bytes[5],
+                    button_count: bytes[6],
+                })
+            }
+            Command::GetCpi => Reply::Cpi(u16::from_le_bytes(fixed(payload)?)),
+            Command::GetOrientation => Reply::Orientation(fixed::<1>(payload)?[0]),
+            Command::GetPollingRate => Reply::PollingRate(fixed::<1>(payload)?[0]),
+            Command::GetLiftConfig => Reply::LiftConfig(decode_lift_config(payload)?),
+            Command::GetButtonAction => {
+                let (button, action) = decode_button_action(payload)?;
+                Reply::ButtonAction(button, action)
+            }
+            Command::GetAngleCorrection => {
+                Reply::AngleCorrection(i16::from_le_bytes(fixed(payload)?))
+            }
+            Command::GetAngleSnap => Reply::AngleSnap(decode_angle_snap(payload)?),
+            Command::GetPowerMode => Reply::PowerMode(fixed(payload)?),
+            Command::ReadRegister => {
+                let [address, value] = fixed(payload)?;
+                Reply::Register { address, value }
+            }
+            Command::ReadFrame => {
+                let (offset, data) = payload
+                    .split_first_chunk::<2>()
+                    .ok_or(DecodeError::InvalidPayload)?;
+                if data.len() > FRAME_CHUNK_SIZE {
+                    return Err(DecodeError::InvalidPayload);
+                }
+                let mut pixels = [0; FRAME_CHUNK_SIZE];
+                pixels[..data.len()].copy_from_slice(data);
+                Reply::FrameChunk(FrameChunk {
+                    offset: u16::from_le_bytes(*offset),
+                    len: data.len() as u8,
+                    pixels,
+                })
+            }
+            Command::SetCpi
+            | Command::SetOrientation
+            | Command::SetPollingRate
+            | Command::SetLiftConfig
+            | Command::SetButtonAction
+            | Command::SetAngleCorrection
+            | Command::SetAngleSnap
+            | Command::SetPowerMode
+            | Command::SaveSettings
+            | Command::CaptureFrame => {
+                fixed::<0>(payload)?;
+                Reply::Done
+            }
+        };
+        Ok(Self {
+            command,
+            result: Ok(reply),
+        })
+    }
+}
+
+/// Error response for a request that couldn't be decoded, echoing its raw command byte.
+pub fn encode_error(command: u8, status: Status) -> [u8; REPORT_SIZE] {
+    encode_report(command, status as u8, &Payload::new())
+}
+
+struct Payload {
+    bytes: [u8; MAX_PAYLOAD_SIZE],
+    len: usize,
+}
+
+impl Payload {
+    fn new() -> Self {
+        Self {
+            bytes: [0; MAX_PAYLOAD_SIZE],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+}
+
+fn encode_report(command: u8, status: u8, payload: &Payload) -> [u8; REPORT_SIZE] {
+    let mut report = [0; REPORT_SIZE];
+    report[0] = PROTOCOL_VERSION;
+    report[1] = command;
+    report[2] = status;
+    report[3] = payload.len as u8;
+    report[HEADER_SIZE..HEADER_SIZE + payload.len].copy_from_slice(&payload.bytes[..payload.len]);
+    report
+}
+
+/// Splits a report into command, status and payload.
+fn decode_report(report: &[u8]) -> Result<(u8, u8, &[u8]), DecodeError> {
+    let (header, rest) = report
+        .split_first_chunk::<HEADER_SIZE>()
+        .ok_or(DecodeError::Truncated)?;
+    let [version, command, status, len] = *header;
+    if version != PROTOCOL_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let payload = rest.get(..len as usize).ok_or(DecodeError::Truncated)?;
+    Ok((command, status, payload))
+}
+
+/// The payload as exactly `N` bytes.
+fn fixed<const N: usize>(payload: &[u8]) -> Result<[u8; N], DecodeError> {
+    payload.try_into().map_err(|_| DecodeError::InvalidPayload)
+}
+
+fn encode_lift_config(payload: &mut Payload, lift_config: LiftConfig) {
+    payload.push(&[
+        lift_config.lift_height,
+        lift_config.squal_threshold.unwrap_or(NONE),
+    ]);
+}
+
+fn decode_lift_config(payload: &[u8]) -> Result<LiftConfig, DecodeError> {
+    let [lift_height, squal_threshold] = fixed(payload)?;
+    Ok(LiftConfig {
+        lift_height,
+        squal_threshold: (squal_threshold != NONE).then_some(squal_threshold),
+    })
+}
+
+fn encode_angle_snap(payload: &mut Payload, angle_snap: AngleSnap) {
+    payload.push(&[
+        angle_snap.sensor as u8,
+        angle_snap.axis_threshold.unwrap_or(NONE),
+    ]);
+}
+
+fn decode_angle_snap(payload: &[u8]) -> Result<AngleSnap, DecodeError> {
+    let [sensor, axis_threshold] = fixed(payload)?;
+    Ok(AngleSnap {
+        sensor: match sensor {
+            0 => false,
+            1 => true,
+            _ => return Err(DecodeError::InvalidPayload),
+        },
+        axis_threshold: (axis_threshold != NONE).then_some(axis_threshold),
+    })
+}
+
+fn decode_button_action(payload: &[u8]) -> Result<(u8, [u8; BUTTON_ACTION_SIZE]), DecodeError> {
+    let [button, action @ ..] = fixed::<{ 1 + BUTTON_ACTION_SIZE }>(payload)?;
+    Ok((button, action))
+}
@@ -0,0 +1,97 @@
+use pmw3360_protocol::message::{
+    encode_error, AngleSnap, Command, DecodeError, LiftConfig, Reply, Request, Response, Status,
+    PROTOCOL_VERSION, REPORT_SIZE,
+};
+
+#[test]
+fn requests_round_trip_through_their_reports() {
+    let requests = [
+        Request::GetDeviceInfo,
+        Request::SetCpi(1600),
+        Request::SetOrientation(0b1_0110),
+        Request::SetAngleCorrection(-125),
+        Request::SetAngleSnap(AngleSnap {
+            sensor: true,
+            axis_threshold: None,
+        }),
+        Request::SetPollingRate(2),
+        Request::SetPowerMode([1, 0x0a, 0x00, 0x00, 0x03, 0x63, 0x00, 0x09, 0xf3, 0x01]),
+        Request::SetLiftConfig(LiftConfig {
+            lift_height: 3,
+            squal_threshold: Some(16),
+        }),
+        Request::SetButtonAction(4, [3, 0x01, 0x04, 0]),
+        Request::ReadRegister(0x42),
+        Request::ReadFrame { offset: 1248 },
+    ];
+
+    for request in requests {
+        let report = request.encode();
+        assert_eq!(report[..2], [PROTOCOL_VERSION, request.command() as u8]);
+        assert_eq!(Request::decode(&report), Ok(request));
+    }
+}
+
+#[test]
+fn responses_round_trip_through_their_reports() {
+    let responses = [
+        Response {
+            command: Command::GetLiftConfig,
+            result: Ok(Reply::LiftConfig(LiftConfig {
+                lift_height: 2,
+                squal_threshold: None,
+            })),
+        },
+        Response {
+            command: Command::GetButtonAction,
+            result: Ok(Reply::ButtonAction(1, [1, 2, 0, 0])),
+        },
+        Response {
+            command: Command::SetCpi,
+            result: Err(Status::InvalidArgument),
+        },
+    ];
+
+    for response in responses {
+        assert_eq!(Response::decode(&response.encode()), Ok(response));
+    }
+}
+
+#[test]
+fn malformed_reports_are_rejected() {
+    let report = Request::SetCpi(800).encode();
+
+    assert_eq!(Request::decode(&report[..3]), Err(DecodeError::Truncated));
+    assert_eq!(Request::decode(&report[..5]), Err(DecodeError::Truncated));
+
+    let mut wrong_length = report;
+    wrong_length[3] = 1;
+    assert_eq!(
+        Request::decode(&wrong_length),
+        Err(DecodeError::InvalidPayload)
+    );
+
+    let mut not_a_bool = Request::SetAngleSnap(AngleSnap {
+        sensor: true,
+        axis_threshold: Some(10),
+    })
+    .encode();
+    not_a_bool[4] = 2;
+    assert_eq!(
+        Request::decode(&not_a_bool),
+        Err(DecodeError::InvalidPayload)
+    );
+
+    let mut unknown = encode_error(0x7f, Status::NoFrame);
+    assert_eq!(
+        Response::decode(&unknown),
+        Err(DecodeError::UnknownCommand(0x7f))
+    );
+    unknown[1] = Command::GetCpi as u8;
+    unknown[2] = 0x7f;
+    assert_eq!(
+        Response::decode(&unknown),
+        Err(DecodeError::UnknownStatus(0x7f))
+    );
+    assert_eq!(unknown.len(), REPORT_SIZE);
+}
@@ -0,0 +1,209 @@
+//! Command line parsing and the commands that run against a connected mouse.
+
+use crate::client::{Client, ClientError};
+use crate::setting::{self, Setting};
+use crate::transport::Transport;
+use pmw3360_mouse::registers::Register;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: pmwctl [--device PATH] COMMAND
+
+Commands:
+  list                 List connected mice
+  info                 Show the firmware and sensor versions
+  get [SETTING]        Show one or all settings
+  set SETTING VALUE    Change a setting until the next reset
+  save                 Keep the current settings across resets
+  registers            Dump the sensor registers
+  export FILE          Write all settings to FILE
+  import FILE          Apply and save the settings in FILE
+  capture FILE         Capture a raw sensor image as PGM
+
+Settings:
+  cpi                  100 to 12000 in steps of 100
+  orientation          0, 90, 180 or 270, then optionally swap-xy, invert-x, invert-y
+  angle-correction     -180.0 to 180.0 (degrees), fine mounting angle correction
+  angle-snap           on or off, the sensor's snapping to the axes
+  axis-snap            none or 0 to 44 (degrees), software snapping to the axes
+  polling-rate         125, 250, 500 or 1000 (Hz), used from the next boot after save
+  lift-height          2 or 3 (mm)
+  squal-threshold      none or 0 to 254
+  power-mode           performance, balanced, power-saver or
+                       custom:REST:RUN_DOWNSHIFT:REST1_RATE:REST1_DOWNSHIFT:REST2_RATE:
+                       REST2_DOWNSHIFT:REST3_RATE, REST on or off, the rest register values
+  button.N             disabled, mouse:BUTTON, keyboard:MODIFIERS:KEY, consumer:USAGE,
+                       cpi-up or cpi-down
+";
+
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments don't make a command.
+    Usage(String),
+    Io(io::Error),
+    Client(ClientError),
+    NoDevice,
+    UnknownSetting(String),
+    SettingsFile {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Io(error) => write!(f, "{}", error),
+            CliError::Client(error) => write!(f, "{}", error),
+            CliError::NoDevice => write!(f, "No mouse found"),
+            CliError::UnknownSetting(name) => write!(f, "Unknown setting {}", name),
+            CliError::SettingsFile {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+impl From<ClientError> for CliError {
+    fn from(error: ClientError) -> Self {
+        CliError::Client(error)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Invocation {
+    Help,
+    List,
+    Run {
+        /// Hidraw node to use instead of the first mouse found.
+        device: Option<PathBuf>,
+        action: Action,
+    },
+}
+
+/// A command that talks to a mouse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Info,
+    Get(Option<String>),
+    Set(Setting),
+    Save,
+    Registers,
+    Export(PathBuf),
+    Import(PathBuf),
+    Capture(PathBuf),
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args(args: &[String]) -> Result<Invocation, CliError> {
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let mut device = None;
+    if let Some(index) = args.iter().position(|&arg| arg == "--device") {
+        let path = args
+            .get(index + 1)
+            .ok_or_else(|| CliError::Usage("--device needs a path".into()))?;
+        device = Some(PathBuf::from(path));
+        args.drain(index..index + 2);
+    }
+
+    let action = match args.as_slice() {
+        [] | ["help"] | ["--help"] | ["-h"] => return Ok(Invocation::Help),
+        ["list"] => return Ok(Invocation::List),
+        ["info"] => Action::Info,
+        ["get"] => Action::Get(None),
+        ["get", name] => Action::Get(Some(name.to_string())),
+        ["set", name, value @ ..] if !value.is_empty() => {
+            Action::Set(Setting::parse(name, &value.join(" ")).map_err(CliError::Usage)?)
+        }
+        ["save"] => Action::Save,
+        ["registers"] => Action::Registers,
+        ["export", path] => Action::Export(path.into()),
+        ["import", path] => Action::Import(path.into()),
+        ["capture", path] => Action::Capture(path.into()),
+        _ => {
+            return Err(CliError::Usage(format!(
+                "Invalid command {}",
+                args.join(" ")
+            )))
+        }
+    };
+    Ok(Invocation::Run { device, action })
+}
+
+pub fn execute<T: Transport>(
+    action: &Action,
+    client: &mut Client<T>,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    match action {
+        Action::Info => {
+            let info = client.device_info()?;
+            let [major, minor, patch] = info.firmware_version;
+            writeln!(out, "firmware: {}.{}.{}", major, minor, patch)?;
+            writeln!(out, "product id: {:#04x}", info.product_id)?;
+            writeln!(out, "revision id: {:#04x}", info.revision_id)?;
+            writeln!(out, "srom id: {:#04x}", info.srom_id)?;
+            writeln!(out, "buttons: {}", info.button_count)?;
+        }
+        Action::Get(name) => {
+            let settings = client.settings()?;
+            let mut found = false;
+            for setting in settings
+                .iter()
+                .filter(|setting| name.as_ref().is_none_or(|name| *name == setting.name()))
+            {
+                writeln!(out, "{} = {}", setting.name(), setting.value())?;
+                found = true;
+            }
+            if !found {
+                return Err(CliError::UnknownSetting(name.clone().unwrap_or_default()));
+            }
+        }
+        Action::Set(setting) => client.set(*setting)?,
+        Action::Save => client.save()?,
+        Action::Registers => {
+            for register in Register::ALL {
+                if register.is_command() || register.latches_motion() {
+                    continue;
+                }
+                let value = client.read_register(register.address())?;
+                writeln!(
+                    out,
+                    "{:#04x} {:<24} {:#04x}",
+                    register.address(),
+                    format!("{:?}", register),
+                    value
+                )?;
+            }
+        }
+        Action::Export(path) => fs::write(path, setting::format_file(&client.settings()?))?,
+        Action::Import(path) => {
+            let file = fs::read_to_string(path)?;
+            let settings =
+                setting::parse_file(&file).map_err(|(line, message)| CliError::SettingsFile {
+                    path: path.clone(),
+                    line,
+                    message,
+                })?;
+            for setting in settings {
+                client.set(setting)?;
+            }
+            client.save()?;
+        }
+        Action::Capture(path) => fs::write(path, client.capture_frame()?.to_pgm())?,
+    }
+    Ok(())
+}
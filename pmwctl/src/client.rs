@@ -0,0 +1,224 @@
+use crate::setting::Setting;
+use crate::transport::Transport;
+use pmw3360_mouse::button_map::ButtonAction;
+use pmw3360_mouse::orientation::Orientation;
+use pmw3360_mouse::polling_rate::PollingRate;
+use pmw3360_mouse::power_mode::PowerMode;
+use pmw3360_mouse::registers::LiftHeight;
+use pmw3360_mouse::sensor_image::{SensorImage, IMAGE_PIXELS};
+use pmw3360_protocol::message::{
+    AngleSnap, Command, DecodeError, DeviceInfo, LiftConfig, Reply, Request, Response, Status,
+};
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(io::Error),
+    /// The response isn't a valid report.
+    Decode(DecodeError),
+    /// The mouse refused the request.
+    Device(Status),
+    /// The response doesn't fit the request, for a command of `Command`.
+    UnexpectedResponse(Command),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(error) => write!(f, "{}", error),
+            ClientError::Decode(error) => write!(f, "Invalid response: {:?}", error),
+            ClientError::Device(status) => write!(f, "The mouse refused: {:?}", status),
+            ClientError::UnexpectedResponse(command) => {
+                write!(f, "Unexpected response to {:?}", command)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> Self {
+        ClientError::Io(error)
+    }
+}
+
+/// Typed requests to a mouse over `T`.
+pub struct Client<T> {
+    transport: T,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn release(self) -> T {
+        self.transport
+    }
+
+    pub fn request(&mut self, request: Request) -> Result<Reply, ClientError> {
+        let report = self.transport.exchange(&request.encode())?;
+        let response = Response::decode(&report).map_err(ClientError::Decode)?;
+        if response.command != request.command() {
+            return Err(ClientError::UnexpectedResponse(response.command));
+        }
+        response.result.map_err(ClientError::Device)
+    }
+
+    pub fn device_info(&mut self) -> Result<DeviceInfo, ClientError> {
+        match self.request(Request::GetDeviceInfo)? {
+            Reply::DeviceInfo(device_info) => Ok(device_info),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetDeviceInfo)),
+        }
+    }
+
+    pub fn cpi(&mut self) -> Result<u16, ClientError> {
+        match self.request(Request::GetCpi)? {
+            Reply::Cpi(cpi) => Ok(cpi),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetCpi)),
+        }
+    }
+
+    pub fn orientation(&mut self) -> Result<Orientation, ClientError> {
+        match self.request(Request::GetOrientation)? {
+            Reply::Orientation(orientation) => Ok(orientation.into()),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetOrientation)),
+        }
+    }
+
+    pub fn polling_rate(&mut self) -> Result<PollingRate, ClientError> {
+        match self.request(Request::GetPollingRate)? {
+            Reply::PollingRate(interval_ms) => {
+                PollingRate::try_from(interval_ms).map_err(|_| invalid_value())
+            }
+            _ => Err(ClientError::UnexpectedResponse(Command::GetPollingRate)),
+        }
+    }
+
+    pub fn lift_config(&mut self) -> Result<LiftConfig, ClientError> {
+        match self.request(Request::GetLiftConfig)? {
+            Reply::LiftConfig(lift_config) => Ok(lift_config),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetLiftConfig)),
+        }
+    }
+
+    pub fn button_action(&mut self, button: u8) -> Result<ButtonAction, ClientError> {
+        match self.request(Request::GetButtonAction(button))? {
+            Reply::ButtonAction(reply_button, action) if reply_button == button => {
+                ButtonAction::from_bytes(action).ok_or_else(invalid_value)
+            }
+            _ => Err(ClientError::UnexpectedResponse(Command::GetButtonAction)),
+        }
+    }
+
+    /// Mounting angle correction in tenths of a degree.
+    pub fn angle_correction(&mut self) -> Result<i16, ClientError> {
+        match self.request(Request::GetAngleCorrection)? {
+            Reply::AngleCorrection(decidegrees) => Ok(decidegrees),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetAngleCorrection)),
+        }
+    }
+
+    pub fn angle_snap(&mut self) -> Result<AngleSnap, ClientError> {
+        match self.request(Request::GetAngleSnap)? {
+            Reply::AngleSnap(angle_snap) => Ok(angle_snap),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetAngleSnap)),
+        }
+    }
+
+    pub fn power_mode(&mut self) -> Result<PowerMode, ClientError> {
+        match self.request(Request::GetPowerMode)? {
+            Reply::PowerMode(power_mode) => Ok(PowerMode::from_bytes(&power_mode)),
+            _ => Err(ClientError::UnexpectedResponse(Command::GetPowerMode)),
+        }
+    }
+
+    pub fn read_register(&mut self, address: u8) -> Result<u8, ClientError> {
+        match self.request(Request::ReadRegister(address))? {
+            Reply::Register {
+                address: reply_address,
+                value,
+            } if reply_address == address => Ok(value),
+            _ => Err(ClientError::UnexpectedResponse(Command::ReadRegister)),
+        }
+    }
+
+    /// Every setting the mouse has, in the order of a settings file.
+    pub fn settings(&mut self) -> Result<Vec<Setting>, ClientError> {
+        let angle_snap = self.angle_snap()?;
+        let lift_config = self.lift_config()?;
+        let mut settings = vec![
+            Setting::Cpi(self.cpi()?),
+            Setting::Orientation(self.orientation()?),
+            Setting::AngleCorrection(self.angle_correction()?),
+            Setting::AngleSnap(angle_snap.sensor),
+            Setting::AxisSnap(angle_snap.axis_threshold),
+            Setting::PollingRate(self.polling_rate()?),
+            Setting::LiftHeight(
+                LiftHeight::try_from(lift_config.lift_height).map_err(|_| invalid_value())?,
+            ),
+            Setting::SqualThreshold(lift_config.squal_threshold),
+            Setting::PowerMode(self.power_mode()?),
+        ];
+        for button in 0..self.device_info()?.button_count {
+            settings.push(Setting::Button(button, self.button_action(button)?));
+        }
+        Ok(settings)
+    }
+
+    /// Changes one setting until the next reset.
+    pub fn set(&mut self, setting: Setting) -> Result<(), ClientError> {
+        let request = match setting {
+            Setting::Cpi(cpi) => Request::SetCpi(cpi),
+            Setting::Orientation(orientation) => Request::SetOrientation(orientation.into()),
+            Setting::AngleCorrection(decidegrees) => Request::SetAngleCorrection(decidegrees),
+            Setting::AngleSnap(sensor) => Request::SetAngleSnap(AngleSnap {
+                sensor,
+                ..self.angle_snap()?
+            }),
+            Setting::AxisSnap(axis_threshold) => Request::SetAngleSnap(AngleSnap {
+                axis_threshold,
+                ..self.angle_snap()?
+            }),
+            Setting::PollingRate(polling_rate) => Request::SetPollingRate(polling_rate.into()),
+            Setting::LiftHeight(lift_height) => Request::SetLiftConfig(LiftConfig {
+                lift_height: lift_height.into(),
+                ..self.lift_config()?
+            }),
+            Setting::SqualThreshold(squal_threshold) => Request::SetLiftConfig(LiftConfig {
+                squal_threshold,
+                ..self.lift_config()?
+            }),
+            Setting::PowerMode(power_mode) => Request::SetPowerMode(power_mode.to_bytes()),
+            Setting::Button(button, action) => Request::SetButtonAction(button, action.to_bytes()),
+        };
+        self.request(request).map(|_| ())
+    }
+
+    pub fn save(&mut self) -> Result<(), ClientError> {
+        self.request(Request::SaveSettings).map(|_| ())
+    }
+
+    /// Captures a raw image and reads it out. The mouse doesn't track for a moment while it
+    /// restarts navigation.
+    pub fn capture_frame(&mut self) -> Result<SensorImage, ClientError> {
+        self.request(Request::CaptureFrame)?;
+
+        let mut pixels = Vec::with_capacity(IMAGE_PIXELS);
+        while pixels.len() < IMAGE_PIXELS {
+            let offset = pixels.len() as u16;
+            match self.request(Request::ReadFrame { offset })? {
+                Reply::FrameChunk(chunk) if chunk.offset == offset && chunk.len > 0 => {
+                    pixels.extend_from_slice(chunk.pixels())
+                }
+                _ => return Err(ClientError::UnexpectedResponse(Command::ReadFrame)),
+            }
+        }
+        Ok(pixels.into())
+    }
+}
+
+/// A reply that decoded, but carries a value the settings can't hold.
+fn invalid_value() -> ClientError {
+    ClientError::Decode(DecodeError::InvalidPayload)
+}
@@ -0,0 +1,85 @@
+//! Access to the configuration interface through Linux hidraw nodes.
+
+use crate::transport::Transport;
+use pmw3360_protocol::message::{REPORT_DESCRIPTOR, REPORT_SIZE};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const SYSFS_HIDRAW: &str = "/sys/class/hidraw";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HidrawDevice {
+    /// Device node, such as `/dev/hidraw3`.
+    pub path: PathBuf,
+    /// Product name the mouse reported.
+    pub name: String,
+}
+
+/// Hidraw nodes of configuration interfaces, recognised by their report descriptor.
+pub fn list_devices() -> io::Result<Vec<HidrawDevice>> {
+    let entries = match fs::read_dir(SYSFS_HIDRAW) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut devices = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let device = entry.path().join("device");
+        // Other users' devices may not be readable, they can't be ours either way.
+        let Ok(report_descriptor) = fs::read(device.join("report_descriptor")) else {
+            continue;
+        };
+        if report_descriptor != REPORT_DESCRIPTOR {
+            continue;
+        }
+
+        let name = fs::read_to_string(device.join("uevent"))
+            .ok()
+            .and_then(|uevent| {
+                uevent
+                    .lines()
+                    .find_map(|line| line.strip_prefix("HID_NAME="))
+                    .map(String::from)
+            })
+            .unwrap_or_default();
+        devices.push(HidrawDevice {
+            path: Path::new("/dev").join(entry.file_name()),
+            name,
+        });
+    }
+    devices.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(devices)
+}
+
+pub struct HidrawTransport {
+    file: File,
+}
+
+impl HidrawTransport {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Self { file })
+    }
+}
+
+impl Transport for HidrawTransport {
+    fn exchange(&mut self, request: &[u8; REPORT_SIZE]) -> io::Result<[u8; REPORT_SIZE]> {
+        // hidraw takes the report ID first, 0 for interfaces without numbered reports.
+        let mut report = [0; REPORT_SIZE + 1];
+        report[1..].copy_from_slice(request);
+        self.file.write_all(&report)?;
+
+        let mut response = [0; REPORT_SIZE];
+        let length = self.file.read(&mut response)?;
+        if length != REPORT_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Short response of {} bytes", length),
+            ));
+        }
+        Ok(response)
+    }
+}
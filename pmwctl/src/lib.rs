@@ -0,0 +1,12 @@
+//! Host-side configuration of the mouse over the protocol in `pmw3360_protocol`.
+//!
+//! The `pmwctl` binary talks to the mouse through Linux hidraw, see [`hidraw`]. Everything above
+//! the [`transport::Transport`] trait is independent of it, so [`model::FirmwareModel`] can stand
+//! in for a real mouse in tests.
+
+pub mod cli;
+pub mod client;
+pub mod hidraw;
+pub mod model;
+pub mod setting;
+pub mod transport;
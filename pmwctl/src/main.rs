@@ -0,0 +1,44 @@
+use pmwctl::cli::{self, CliError, Invocation, USAGE};
+use pmwctl::client::Client;
+use pmwctl::hidraw::{self, HidrawTransport};
+use std::env;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("pmwctl: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let mut out = io::stdout().lock();
+    match cli::parse_args(args)? {
+        Invocation::Help => out.write_all(USAGE.as_bytes())?,
+        Invocation::List => {
+            for device in hidraw::list_devices()? {
+                writeln!(out, "{}\t{}", device.path.display(), device.name)?;
+            }
+        }
+        Invocation::Run { device, action } => {
+            let path = match device {
+                Some(path) => path,
+                None => {
+                    hidraw::list_devices()?
+                        .into_iter()
+                        .next()
+                        .ok_or(CliError::NoDevice)?
+                        .path
+                }
+            };
+            let mut client = Client::new(HidrawTransport::open(&path)?);
+            cli::execute(&action, &mut client, &mut out)?;
+        }
+    }
+    Ok(())
+}
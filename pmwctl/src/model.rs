@@ -0,0 +1,79 @@
+//! In-process stand-in for a mouse running the firmware, on the simulated sensor and flash. It
+//! boots and answers requests the way the firmware does.
+
+use crate::transport::Transport;
+use pmw3360_mouse::config_server::ConfigServer;
+use pmw3360_mouse::mouse::Mouse;
+use pmw3360_mouse::pmw_driver::PmwDriver;
+use pmw3360_mouse::settings::Settings;
+use pmw3360_mouse::settings_store::SettingsStore;
+use pmw3360_mouse::sim::{Pmw3360Sim, SimChipEnable, SimDelay, SimSpi};
+use pmw3360_mouse::sim_flash::SimFlash;
+use pmw3360_protocol::message::REPORT_SIZE;
+use std::io;
+
+/// Buttons of the modelled board, like the firmware's.
+pub const MODEL_BUTTONS: usize = 5;
+pub const MODEL_FIRMWARE_VERSION: [u8; 3] = [0, 1, 0];
+
+type SimMouse = Mouse<SimSpi, SimChipEnable, SimDelay, SimFlash, MODEL_BUTTONS>;
+
+pub struct FirmwareModel {
+    sim: Pmw3360Sim,
+    mouse: SimMouse,
+    config_server: ConfigServer,
+}
+
+impl FirmwareModel {
+    /// A freshly flashed mouse, without saved settings.
+    pub fn new() -> Self {
+        Self::boot(Pmw3360Sim::new(), SimFlash::new())
+    }
+
+    /// Power cycles the mouse, dropping settings that weren't saved.
+    pub fn reboot(self) -> Self {
+        let (_, settings_store) = self.mouse.release();
+        Self::boot(self.sim, settings_store.release())
+    }
+
+    pub fn sim(&self) -> &Pmw3360Sim {
+        &self.sim
+    }
+
+    pub fn settings(&self) -> &Settings {
+        self.mouse.settings()
+    }
+
+    fn boot(sim: Pmw3360Sim, flash: SimFlash) -> Self {
+        let mut settings_store = SettingsStore::new(flash);
+        let settings = settings_store
+            .load()
+            .expect("Simulated flash is readable.")
+            .unwrap_or_default();
+
+        let mut pmw_driver = PmwDriver::new(sim.spi(), sim.chip_enable(), sim.delay());
+        let sensor_info = pmw_driver.init().expect("Simulated sensor initialises.");
+        let mut mouse = Mouse::new(pmw_driver, sensor_info, settings_store, settings);
+        mouse
+            .configure_sensor()
+            .expect("Saved settings are accepted by the sensor.");
+
+        Self {
+            sim,
+            mouse,
+            config_server: ConfigServer::new(MODEL_FIRMWARE_VERSION),
+        }
+    }
+}
+
+impl Default for FirmwareModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for FirmwareModel {
+    fn exchange(&mut self, request: &[u8; REPORT_SIZE]) -> io::Result<[u8; REPORT_SIZE]> {
+        Ok(self.config_server.handle_report(&mut self.mouse, request))
+    }
+}
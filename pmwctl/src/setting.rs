@@ -0,0 +1,322 @@
+//! Text form of the settings, shared by the command line and settings files.
+//!
+//! A settings file holds one `name = value` line per setting, as printed by `pmwctl get`, with
+//! `#` starting a comment. Settings missing from a file are left as they are on import.
+
+use pmw3360_mouse::axis_snap::MAX_AXIS_SNAP_THRESHOLD;
+use pmw3360_mouse::button_map::ButtonAction;
+use pmw3360_mouse::fine_rotation::MAX_ANGLE_CORRECTION;
+use pmw3360_mouse::orientation::{Orientation, Rotation};
+use pmw3360_mouse::polling_rate::PollingRate;
+use pmw3360_mouse::power_mode::PowerMode;
+use pmw3360_mouse::registers::LiftHeight;
+use std::fmt::Write as _;
+
+const MOUSE_BUTTON_NAMES: [&str; 5] = ["left", "right", "middle", "back", "forward"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    Cpi(u16),
+    Orientation(Orientation),
+    /// Tenths of a degree.
+    AngleCorrection(i16),
+    /// The sensor's Angle_Snap.
+    AngleSnap(bool),
+    /// Software snapping threshold in degrees.
+    AxisSnap(Option<u8>),
+    PollingRate(PollingRate),
+    LiftHeight(LiftHeight),
+    SqualThreshold(Option<u8>),
+    PowerMode(PowerMode),
+    Button(u8, ButtonAction),
+}
+
+impl Setting {
+    pub fn parse(name: &str, value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let setting = match name {
+            "cpi" => Setting::Cpi(parse_number(value)?),
+            "orientation" => Setting::Orientation(parse_orientation(value)?),
+            "angle-correction" => Setting::AngleCorrection(parse_decidegrees(value)?),
+            "angle-snap" => Setting::AngleSnap(match value {
+                "on" => true,
+                "off" => false,
+                _ => return Err(format!("Expected on or off, not {}", value)),
+            }),
+            "axis-snap" => Setting::AxisSnap(match value {
+                "none" => None,
+                _ => match parse_number(value)? {
+                    threshold if threshold <= MAX_AXIS_SNAP_THRESHOLD => Some(threshold),
+                    _ => return Err(format!("Unsupported axis snap threshold {}", value)),
+                },
+            }),
+            "polling-rate" => {
+                Setting::PollingRate(match parse_number::<u16>(value.trim_end_matches("Hz"))? {
+                    125 => PollingRate::Hz125,
+                    250 => PollingRate::Hz250,
+                    500 => PollingRate::Hz500,
+                    1000 => PollingRate::Hz1000,
+                    _ => return Err(format!("Unsupported polling rate {}", value)),
+                })
+            }
+            "lift-height" => Setting::LiftHeight(
+                LiftHeight::try_from(parse_number::<u8>(value.trim_end_matches("mm"))?)
+                    .map_err(|_| format!("Unsupported lift height {}", value))?,
+            ),
+            "squal-threshold" => Setting::SqualThreshold(match value {
+                "none" => None,
+                _ => match parse_number(value)? {
+                    // Encodes `None` on the wire.
+                    u8::MAX => return Err(format!("Unsupported SQUAL threshold {}", value)),
+                    threshold => Some(threshold),
+                },
+            }),
+            "power-mode" => Setting::PowerMode(parse_power_mode(value)?),
+            _ => {
+                let button = name
+                    .strip_prefix("button.")
+                    .ok_or_else(|| format!("Unknown setting {}", name))?;
+                Setting::Button(parse_number(button)?, parse_button_action(value)?)
+            }
+        };
+        Ok(setting)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Setting::Cpi(_) => "cpi".into(),
+            Setting::Orientation(_) => "orientation".into(),
+            Setting::AngleCorrection(_) => "angle-correction".into(),
+            Setting::AngleSnap(_) => "angle-snap".into(),
+            Setting::AxisSnap(_) => "axis-snap".into(),
+            Setting::PollingRate(_) => "polling-rate".into(),
+            Setting::LiftHeight(_) => "lift-height".into(),
+            Setting::SqualThreshold(_) => "squal-threshold".into(),
+            Setting::PowerMode(_) => "power-mode".into(),
+            Setting::Button(button, _) => format!("button.{}", button),
+        }
+    }
+
+    /// The value in the form `parse` takes.
+    pub fn value(&self) -> String {
+        match *self {
+            Setting::Cpi(cpi) => cpi.to_string(),
+            Setting::Orientation(orientation) => format_orientation(orientation),
+            Setting::AngleCorrection(decidegrees) => format_decidegrees(decidegrees),
+            Setting::AngleSnap(true) => "on".into(),
+            Setting::AngleSnap(false) => "off".into(),
+            Setting::AxisSnap(None) => "none".into(),
+            Setting::AxisSnap(Some(threshold)) => threshold.to_string(),
+            Setting::PollingRate(polling_rate) => polling_rate.hz().to_string(),
+            Setting::LiftHeight(lift_height) => u8::from(lift_height).to_string(),
+            Setting::SqualThreshold(None) => "none".into(),
+            Setting::SqualThreshold(Some(threshold)) => threshold.to_string(),
+            Setting::PowerMode(power_mode) => format_power_mode(power_mode),
+            Setting::Button(_, action) => format_button_action(action),
+        }
+    }
+}
+
+pub fn format_file(settings: &[Setting]) -> String {
+    let mut file = String::from("# pmwctl settings\n");
+    for setting in settings {
+        writeln!(file, "{} = {}", setting.name(), setting.value()).unwrap();
+    }
+    file
+}
+
+/// Parses a settings file, reporting errors with their 1-based line number.
+pub fn parse_file(file: &str) -> Result<Vec<Setting>, (usize, String)> {
+    let mut settings = Vec::new();
+    for (index, line) in file.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let setting = line
+            .split_once('=')
+            .ok_or_else(|| "Expected name = value".to_string())
+            .and_then(|(name, value)| Setting::parse(name.trim(), value));
+        settings.push(setting.map_err(|message| (index + 1, message))?);
+    }
+    Ok(settings)
+}
+
+/// Decimal, or hexadecimal with a `0x` prefix.
+fn parse_number<T: TryFrom<u32>>(text: &str) -> Result<T, String> {
+    let number = match text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    number
+        .ok()
+        .and_then(|number| T::try_from(number).ok())
+        .ok_or_else(|| format!("Invalid number {}", text))
+}
+
+/// Rotation in degrees, followed by any of `swap-xy`, `invert-x` and `invert-y`.
+fn parse_orientation(value: &str) -> Result<Orientation, String> {
+    let mut words = value.split_whitespace();
+    let rotation = match words.next() {
+        Some("0") => Rotation::Deg0,
+        Some("90") => Rotation::Deg90,
+        Some("180") => Rotation::Deg180,
+        Some("270") => Rotation::Deg270,
+        _ => return Err(format!("Invalid rotation in {}", value)),
+    };
+
+    let mut orientation = Orientation {
+        rotation,
+        ..Orientation::default()
+    };
+    for flag in words {
+        match flag {
+            "swap-xy" => orientation.swap_xy = true,
+            "invert-x" => orientation.invert_x = true,
+            "invert-y" => orientation.invert_y = true,
+            _ => return Err(format!("Unknown orientation flag {}", flag)),
+        }
+    }
+    Ok(orientation)
+}
+
+/// Degrees with at most one decimal, e.g. `-12.5`.
+fn parse_decidegrees(value: &str) -> Result<i16, String> {
+    let invalid = || format!("Invalid angle {}", value);
+    let (negative, magnitude) = match value.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, value),
+    };
+    let (whole, tenths) = magnitude.split_once('.').unwrap_or((magnitude, "0"));
+    if whole.is_empty() || tenths.len() != 1 {
+        return Err(invalid());
+    }
+    let whole: u16 = whole.parse().map_err(|_| invalid())?;
+    let tenths: u16 = tenths.parse().map_err(|_| invalid())?;
+
+    let decidegrees = whole
+        .checked_mul(10)
+        .and_then(|decidegrees| decidegrees.checked_add(tenths))
+        .and_then(|decidegrees| i16::try_from(decidegrees).ok())
+        .filter(|&decidegrees| decidegrees <= MAX_ANGLE_CORRECTION)
+        .ok_or_else(|| format!("Unsupported angle {}", value))?;
+    Ok(if negative { -decidegrees } else { decidegrees })
+}
+
+fn format_decidegrees(decidegrees: i16) -> String {
+    let sign = if decidegrees < 0 { "-" } else { "" };
+    let magnitude = decidegrees.unsigned_abs();
+    format!("{}{}.{}", sign, magnitude / 10, magnitude % 10)
+}
+
+fn format_orientation(orientation: Orientation) -> String {
+    let mut value = match orientation.rotation {
+        Rotation::Deg0 => "0",
+        Rotation::Deg90 => "90",
+        Rotation::Deg180 => "180",
+        Rotation::Deg270 => "270",
+    }
+    .to_string();
+    for (flag, name) in [
+        (orientation.swap_xy, " swap-xy"),
+        (orientation.invert_x, " invert-x"),
+        (orientation.invert_y, " invert-y"),
+    ] {
+        if flag {
+            value.push_str(name);
+        }
+    }
+    value
+}
+
+/// `performance`, `balanced`, `power-saver`, or
+/// `custom:REST:RUN_DOWNSHIFT:REST1_RATE:REST1_DOWNSHIFT:REST2_RATE:REST2_DOWNSHIFT:REST3_RATE`
+/// with `REST` either `on` or `off` and the rest in the units of the sensor registers.
+fn parse_power_mode(value: &str) -> Result<PowerMode, String> {
+    let invalid = || format!("Invalid power mode {}", value);
+    let fields = match value {
+        "performance" => return Ok(PowerMode::PERFORMANCE),
+        "balanced" => return Ok(PowerMode::BALANCED),
+        "power-saver" => return Ok(PowerMode::POWER_SAVER),
+        _ => value.strip_prefix("custom:").ok_or_else(invalid)?,
+    };
+
+    let mut fields = fields.split(':');
+    let mut field = || fields.next().ok_or_else(invalid);
+    let power_mode = PowerMode {
+        rest_enabled: match field()? {
+            "on" => true,
+            "off" => false,
+            rest => return Err(format!("Expected on or off, not {}", rest)),
+        },
+        run_downshift: parse_number(field()?)?,
+        rest1_rate: parse_number(field()?)?,
+        rest1_downshift: parse_number(field()?)?,
+        rest2_rate: parse_number(field()?)?,
+        rest2_downshift: parse_number(field()?)?,
+        rest3_rate: parse_number(field()?)?,
+    };
+    match fields.next() {
+        Some(_) => Err(invalid()),
+        None => Ok(power_mode),
+    }
+}
+
+fn format_power_mode(power_mode: PowerMode) -> String {
+    match power_mode {
+        PowerMode::PERFORMANCE => "performance".into(),
+        PowerMode::BALANCED => "balanced".into(),
+        PowerMode::POWER_SAVER => "power-saver".into(),
+        _ => format!(
+            "custom:{}:{:#04x}:{:#06x}:{:#04x}:{:#06x}:{:#04x}:{:#06x}",
+            if power_mode.rest_enabled { "on" } else { "off" },
+            power_mode.run_downshift,
+            power_mode.rest1_rate,
+            power_mode.rest1_downshift,
+            power_mode.rest2_rate,
+            power_mode.rest2_downshift,
+            power_mode.rest3_rate,
+        ),
+    }
+}
+
+/// `disabled`, `mouse:BUTTON`, `keyboard:MODIFIERS:KEY`, `consumer:USAGE`, `cpi-up` or
+/// `cpi-down`. Mouse buttons go by name or index, keys and usages by their HID usage IDs.
+fn parse_button_action(value: &str) -> Result<ButtonAction, String> {
+    let mut parts = value.split(':');
+    let action = match (parts.next(), parts.next(), parts.next()) {
+        (Some("disabled"), None, None) => ButtonAction::Disabled,
+        (Some("cpi-up"), None, None) => ButtonAction::CpiUp,
+        (Some("cpi-down"), None, None) => ButtonAction::CpiDown,
+        (Some("mouse"), Some(button), None) => ButtonAction::Mouse(
+            match MOUSE_BUTTON_NAMES.iter().position(|&name| name == button) {
+                Some(index) => index as u8,
+                None => parse_number(button)?,
+            },
+        ),
+        (Some("keyboard"), Some(modifiers), Some(key)) => ButtonAction::Keyboard {
+            modifiers: parse_number(modifiers)?,
+            key: parse_number(key)?,
+        },
+        (Some("consumer"), Some(usage), None) => ButtonAction::Consumer(parse_number(usage)?),
+        _ => return Err(format!("Invalid button action {}", value)),
+    };
+    Ok(action)
+}
+
+fn format_button_action(action: ButtonAction) -> String {
+    match action {
+        ButtonAction::Disabled => "disabled".into(),
+        ButtonAction::CpiUp => "cpi-up".into(),
+        ButtonAction::CpiDown => "cpi-down".into(),
+        ButtonAction::Mouse(button) => match MOUSE_BUTTON_NAMES.get(button as usize) {
+            Some(name) => format!("mouse:{}", name),
+            None => format!("mouse:{}", button),
+        },
+        ButtonAction::Keyboard { modifiers, key } => {
+            format!("keyboard:{:#04x}:{:#04x}", modifiers, key)
+        }
+        ButtonAction::Consumer(usage) => format!("consumer:{:#06x}", usage),
+    }
+}
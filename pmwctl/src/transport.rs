@@ -0,0 +1,8 @@
+use pmw3360_protocol::message::REPORT_SIZE;
+use std::io;
+
+/// Carries request reports to the mouse and its responses back.
+pub trait Transport {
+    /// Sends a request and waits for the response to it.
+    fn exchange(&mut self, request: &[u8; REPORT_SIZE]) -> io::Result<[u8; REPORT_SIZE]>;
+}
@@ -0,0 +1,236 @@
+use pmw3360_mouse::button_map::ButtonAction;
+use pmw3360_mouse::consumer_report::VOLUME_UP;
+use pmw3360_mouse::orientation::Rotation;
+use pmw3360_mouse::polling_rate::PollingRate;
+use pmw3360_mouse::power_mode::PowerMode;
+use pmw3360_mouse::registers::{AngleSnap, AngleTune, Config1, Config2, Register};
+use pmw3360_mouse::settings::Settings;
+use pmwctl::cli::{self, CliError, Invocation};
+use pmwctl::client::Client;
+use pmwctl::model::FirmwareModel;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Runs a command line against the model, like the binary does against a real mouse.
+fn run(client: &mut Client<FirmwareModel>, command: &str) -> Result<String, CliError> {
+    let args: Vec<String> = command.split_whitespace().map(String::from).collect();
+    let Invocation::Run { action, .. } = cli::parse_args(&args)? else {
+        panic!("{} doesn't talk to a mouse", command);
+    };
+
+    let mut out = Vec::new();
+    cli::execute(&action, client, &mut out)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("pmwctl-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn set_settings_apply_at_once_and_persist_when_saved() {
+    let mut client = Client::new(FirmwareModel::new());
+
+    run(&mut client, "set cpi 1600").unwrap();
+    run(&mut client, "set orientation 90 invert-y").unwrap();
+    run(&mut client, "set button.2 consumer:0xe9").unwrap();
+    assert_eq!(run(&mut client, "get cpi").unwrap(), "cpi = 1600\n");
+    let model = client.release();
+    assert_eq!(Config1(model.sim().register(Register::Config1)).cpi(), 1600);
+    assert_eq!(
+        model.settings().button_actions[2],
+        ButtonAction::Consumer(VOLUME_UP)
+    );
+
+    let mut client = Client::new(FirmwareModel::new());
+    run(&mut client, "set polling-rate 250").unwrap();
+    run(&mut client, "set squal-threshold 20").unwrap();
+    run(&mut client, "save").unwrap();
+    run(&mut client, "set lift-height 3").unwrap();
+
+    let model = client.release().reboot();
+    assert_eq!(model.settings().polling_rate, PollingRate::Hz250);
+    assert_eq!(model.settings().lift_squal_threshold, Some(20));
+    assert_eq!(model.settings().lift_height, Settings::DEFAULT.lift_height);
+}
+
+#[test]
+fn angle_correction_splits_between_angle_tune_and_software() {
+    let mut client = Client::new(FirmwareModel::new());
+
+    run(&mut client, "set angle-correction -42.5").unwrap();
+    assert_eq!(
+        run(&mut client, "get angle-correction").unwrap(),
+        "angle-correction = -42.5\n"
+    );
+    assert!(matches!(
+        run(&mut client, "set angle-correction 180.5"),
+        Err(CliError::Usage(_))
+    ));
+    run(&mut client, "save").unwrap();
+
+    let model = client.release().reboot();
+    assert_eq!(model.settings().angle_correction, -425);
+    assert_eq!(
+        AngleTune(model.sim().register(Register::AngleTune)).angle(),
+        -30
+    );
+}
+
+#[test]
+fn sensor_and_software_angle_snapping_are_set_separately() {
+    let mut client = Client::new(FirmwareModel::new());
+
+    run(&mut client, "set angle-snap on").unwrap();
+    run(&mut client, "set axis-snap 10").unwrap();
+    assert!(matches!(
+        run(&mut client, "set axis-snap 45"),
+        Err(CliError::Usage(_))
+    ));
+    assert_eq!(
+        run(&mut client, "get angle-snap").unwrap(),
+        "angle-snap = on\n"
+    );
+    assert_eq!(
+        run(&mut client, "get axis-snap").unwrap(),
+        "axis-snap = 10\n"
+    );
+    run(&mut client, "save").unwrap();
+
+    let model = client.release().reboot();
+    assert!(model.settings().sensor_angle_snap);
+    assert_eq!(model.settings().axis_snap_threshold, Some(10));
+    assert!(AngleSnap(model.sim().register(Register::AngleSnap)).enable());
+
+    let mut client = Client::new(model);
+    run(&mut client, "set angle-snap off").unwrap();
+    let model = client.release();
+    assert!(!AngleSnap(model.sim().register(Register::AngleSnap)).enable());
+    assert_eq!(model.settings().axis_snap_threshold, Some(10));
+}
+
+#[test]
+fn power_mode_presets_rewrite_the_rest_registers() {
+    let mut client = Client::new(FirmwareModel::new());
+    assert_eq!(
+        run(&mut client, "get power-mode").unwrap(),
+        "power-mode = performance\n"
+    );
+
+    run(&mut client, "set power-mode power-saver").unwrap();
+    let model = client.release();
+    let sim = model.sim();
+    let saver = PowerMode::POWER_SAVER;
+    assert!(Config2(sim.register(Register::Config2)).rest_en());
+    assert_eq!(sim.register(Register::RunDownshift), saver.run_downshift);
+    assert_eq!(
+        sim.register(Register::Rest1Downshift),
+        saver.rest1_downshift
+    );
+    assert_eq!(
+        sim.register(Register::Rest2Downshift),
+        saver.rest2_downshift
+    );
+    assert_eq!(
+        [
+            sim.register(Register::Rest3RateLower),
+            sim.register(Register::Rest3RateUpper)
+        ],
+        saver.rest3_rate.to_le_bytes()
+    );
+
+    let mut client = Client::new(model);
+    run(&mut client, "set power-mode balanced").unwrap();
+    let model = client.release();
+    assert!(Config2(model.sim().register(Register::Config2)).rest_en());
+    assert_eq!(
+        model.sim().register(Register::RunDownshift),
+        PowerMode::BALANCED.run_downshift
+    );
+
+    let mut client = Client::new(model);
+    run(
+        &mut client,
+        "set power-mode custom:on:0x14:0:0x02:0x63:0x10:0x1f3",
+    )
+    .unwrap();
+    assert_eq!(
+        run(&mut client, "get power-mode").unwrap(),
+        "power-mode = custom:on:0x14:0x0000:0x02:0x0063:0x10:0x01f3\n"
+    );
+    run(&mut client, "set power-mode performance").unwrap();
+    let model = client.release();
+    assert!(!Config2(model.sim().register(Register::Config2)).rest_en());
+    assert_eq!(model.settings().power_mode, PowerMode::PERFORMANCE);
+}
+
+#[test]
+fn exported_settings_import_into_another_mouse() {
+    let path = temp_path("export.txt");
+    let mut client = Client::new(FirmwareModel::new());
+    run(&mut client, "set orientation 180 swap-xy").unwrap();
+    run(&mut client, "set button.4 cpi-up").unwrap();
+    run(&mut client, "set angle-correction 7.5").unwrap();
+    run(&mut client, "set angle-snap on").unwrap();
+    run(&mut client, "set axis-snap 5").unwrap();
+    run(&mut client, "set power-mode power-saver").unwrap();
+    run(&mut client, &format!("export {}", path.display())).unwrap();
+    let exported = *client.release().settings();
+    assert_ne!(exported, Settings::DEFAULT);
+
+    let file = fs::read_to_string(&path).unwrap();
+    assert!(file.contains("orientation = 180 swap-xy\n"));
+    assert!(file.contains("angle-correction = 7.5\n"));
+    assert!(file.contains("angle-snap = on\n"));
+    assert!(file.contains("axis-snap = 5\n"));
+    assert!(file.contains("power-mode = power-saver\n"));
+    assert!(file.contains("button.0 = mouse:left\n"));
+
+    let mut client = Client::new(FirmwareModel::new());
+    run(&mut client, &format!("import {}", path.display())).unwrap();
+    let model = client.release().reboot();
+    assert_eq!(model.settings(), &exported);
+    assert_eq!(model.settings().orientation.rotation, Rotation::Deg180);
+    assert_eq!(model.settings().button_actions[4], ButtonAction::CpiUp);
+    assert_eq!(model.settings().power_mode, PowerMode::POWER_SAVER);
+    assert!(AngleSnap(model.sim().register(Register::AngleSnap)).enable());
+
+    fs::write(&path, "cpi = 800\nbutton.9 = mouse:left\n").unwrap();
+    let mut client = Client::new(model);
+    assert!(matches!(
+        run(&mut client, &format!("import {}", path.display())),
+        Err(CliError::Client(_))
+    ));
+    fs::write(&path, "cpi = 800\npolling-rate = 300\n").unwrap();
+    assert!(matches!(
+        run(&mut client, &format!("import {}", path.display())),
+        Err(CliError::SettingsFile { line: 2, .. })
+    ));
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        client.release().settings().button_actions[2],
+        Settings::DEFAULT.button_actions[2]
+    );
+}
+
+#[test]
+fn registers_and_frames_are_read_for_diagnostics() {
+    let mut client = Client::new(FirmwareModel::new());
+
+    let registers = run(&mut client, "registers").unwrap();
+    assert!(registers.contains("0x00 ProductId"));
+    assert!(registers.lines().next().unwrap().ends_with("0x42"));
+    assert!(!registers.contains("PowerUpReset"));
+    assert!(!registers.contains("0x02 Motion"));
+    assert!(!registers.contains("DeltaXL"));
+
+    let path = temp_path("frame.pgm");
+    run(&mut client, &format!("capture {}", path.display())).unwrap();
+    let pgm = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(pgm.starts_with(b"P5\n36 36\n255\n"));
+    assert_eq!(pgm.len(), b"P5\n36 36\n255\n".len() + 36 * 36);
+    assert!(client.release().sim().srom_loaded());
+}